@@ -0,0 +1,160 @@
+use lab5_shaders::geometry;
+use lab5_shaders::obj_loader::Obj;
+use lab5_shaders::ply::{self, PlyOptions};
+use lab5_shaders::stl;
+
+const USAGE: &str = "\
+Uso: generate_mesh [tipo] [opciones]
+
+Tipos (por defecto uv-sphere, que regenera assets/sphere.obj):
+  uv-sphere     esfera de latitud/longitud     --radius --rings --sectors
+  icosphere     icosaedro subdividido          --radius --subdivisions
+  cube-sphere   cubo proyectado a la esfera    --radius --subdivisions
+  torus         toro en el plano xz            --radius --inner-radius --rings --sectors
+  annulus       anillo plano de dos caras      --radius --inner-radius --sectors
+  plane         plano cuadrado en xz           --radius (medio lado) --subdivisions
+
+Opciones:
+  -o, --output <archivo>   .obj, .ply o .stl (por defecto assets/<tipo>.obj)
+  --radius <r>             radio (exterior en toro y anillo)
+  --inner-radius <r>       radio del tubo del toro / radio interior del anillo
+  --rings <n>              divisiones de latitud o alrededor del eje
+  --sectors <n>            divisiones de longitud o alrededor del tubo
+  --subdivisions <n>       nivel de subdivisión o resolución por lado
+  --uv                     escribir coordenadas de textura (OBJ y PLY)
+  --tangents               escribir tangentes (solo PLY)
+  --binary                 PLY o STL binario
+  -h, --help               mostrar esta ayuda";
+
+struct Options {
+    kind: String,
+    output: Option<String>,
+    radius: f32,
+    inner_radius: Option<f32>,
+    rings: u32,
+    sectors: u32,
+    subdivisions: Option<u32>,
+    uv: bool,
+    tangents: bool,
+    binary: bool,
+}
+
+fn parse_args() -> Result<Options, String> {
+    let mut options = Options {
+        kind: "uv-sphere".to_string(),
+        output: None,
+        radius: 1.0,
+        inner_radius: None,
+        rings: 30,
+        sectors: 30,
+        subdivisions: None,
+        uv: false,
+        tangents: false,
+        binary: false,
+    };
+
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        let mut value = |name: &str| args.next().ok_or_else(|| format!("{} necesita un valor", name));
+
+        match arg.as_str() {
+            "-h" | "--help" => {
+                println!("{}", USAGE);
+                std::process::exit(0);
+            }
+            "-o" | "--output" => options.output = Some(value(&arg)?),
+            "--radius" => options.radius = parse_number(&arg, &value(&arg)?)?,
+            "--inner-radius" => options.inner_radius = Some(parse_number(&arg, &value(&arg)?)?),
+            "--rings" => options.rings = parse_number(&arg, &value(&arg)?)?,
+            "--sectors" => options.sectors = parse_number(&arg, &value(&arg)?)?,
+            "--subdivisions" => options.subdivisions = Some(parse_number(&arg, &value(&arg)?)?),
+            "--uv" => options.uv = true,
+            "--tangents" => options.tangents = true,
+            "--binary" => options.binary = true,
+            _ if arg.starts_with('-') => return Err(format!("opción desconocida '{}'", arg)),
+            _ => options.kind = arg,
+        }
+    }
+
+    Ok(options)
+}
+
+fn parse_number<T: std::str::FromStr>(name: &str, value: &str) -> Result<T, String> {
+    value
+        .parse()
+        .map_err(|_| format!("{}: '{}' no es un número válido", name, value))
+}
+
+fn generate(options: &Options) -> Result<Obj, String> {
+    let mesh = match options.kind.as_str() {
+        "uv-sphere" => geometry::uv_sphere(options.radius, options.rings, options.sectors),
+        "icosphere" => geometry::icosphere(options.radius, options.subdivisions.unwrap_or(4)),
+        "cube-sphere" => geometry::cube_sphere(options.radius, options.subdivisions.unwrap_or(16)),
+        "torus" => geometry::torus(
+            options.radius,
+            options.inner_radius.unwrap_or(options.radius * 0.25),
+            options.rings,
+            options.sectors,
+        ),
+        "annulus" => geometry::annulus(
+            options.inner_radius.unwrap_or(options.radius * 0.5),
+            options.radius,
+            options.sectors,
+        ),
+        "plane" => geometry::plane(options.radius * 2.0, options.subdivisions.unwrap_or(1)),
+        kind => return Err(format!("tipo de malla desconocido '{}'\n\n{}", kind, USAGE)),
+    };
+    mesh.map_err(|e| format!("{}: {}", options.kind, e))
+}
+
+fn save(mesh: &Obj, filename: &str, options: &Options) -> Result<(), String> {
+    let extension = std::path::Path::new(filename)
+        .extension()
+        .and_then(|e| e.to_str())
+        .unwrap_or("")
+        .to_lowercase();
+
+    if options.tangents && extension != "ply" {
+        return Err("--tangents solo está disponible para archivos .ply".to_string());
+    }
+
+    let result = match extension.as_str() {
+        "obj" => mesh.save(filename, options.uv),
+        "ply" => ply::save(
+            mesh,
+            filename,
+            PlyOptions {
+                binary: options.binary,
+                tex_coords: options.uv,
+                tangents: options.tangents,
+            },
+        ),
+        "stl" => stl::save(mesh, filename, options.binary),
+        _ => return Err(format!("{}: formato no soportado (usa .obj, .ply o .stl)", filename)),
+    };
+    result.map_err(|e| format!("{}: {}", filename, e))
+}
+
+fn main() {
+    let result = parse_args().and_then(|options| {
+        let mesh = generate(&options)?;
+
+        let output = match &options.output {
+            Some(output) => output.clone(),
+            None if options.kind == "uv-sphere" => "assets/sphere.obj".to_string(),
+            None => format!("assets/{}.obj", options.kind),
+        };
+        save(&mesh, &output, &options)?;
+
+        let (vertices, indices) = mesh.get_indexed_vertex_array();
+        println!("Malla creada: {}", output);
+        println!("  Vértices: {}", vertices.len());
+        println!("  Caras: {}", indices.len() / 3);
+        Ok(())
+    });
+
+    if let Err(e) = result {
+        eprintln!("Error: {}", e);
+        std::process::exit(1);
+    }
+}
@@ -1,5 +1,13 @@
-use nalgebra_glm::Vec3;
+use nalgebra_glm::{self as glm, Mat4, Vec3};
 
+// Alcance de la escena alrededor de center: lo que está a más de esto en
+// profundidad queda fuera del volumen de recorte
+const DEPTH_RANGE: f32 = 2.0;
+
+// Observador de la escena. Los cuerpos se ven con una proyección ortográfica
+// que muestra [-1, 1] en x e y alrededor de center (estirada a la ventana,
+// como siempre); el fondo, que está infinitamente lejos, se ve en perspectiva
+// desde eye con el mismo encuadre
 pub struct Camera {
     pub eye: Vec3,
     pub center: Vec3,
@@ -10,4 +18,90 @@ impl Camera {
     pub fn new(eye: Vec3, center: Vec3, up: Vec3) -> Self {
         Camera { eye, center, up }
     }
+
+    pub fn view(&self) -> Mat4 {
+        glm::look_at(&self.eye, &self.center, &self.up)
+    }
+
+    pub fn projection(&self) -> Mat4 {
+        let distance = (self.eye - self.center).norm();
+        glm::ortho_rh_zo(-1.0, 1.0, -1.0, 1.0, distance - DEPTH_RANGE, distance + DEPTH_RANGE)
+    }
+
+    // Del mundo al espacio de recorte (project en shader.wgsl)
+    pub fn view_projection(&self) -> Mat4 {
+        self.projection() * self.view()
+    }
+
+    // Dirección unitaria hacia el observador; con la proyección ortográfica es
+    // la misma desde cualquier punto
+    pub fn view_direction(&self) -> Vec3 {
+        (self.eye - self.center).normalize()
+    }
+
+    // Sólo la orientación de la vista (mundo a cámara), sin la traslación: lo
+    // que está infinitamente lejos, como las estrellas, no se mueve con el
+    // observador pero sí gira con él
+    pub fn view_rotation(&self) -> Mat4 {
+        let mut view = self.view();
+        view[(0, 3)] = 0.0;
+        view[(1, 3)] = 0.0;
+        view[(2, 3)] = 0.0;
+        view
+    }
+
+    // Tangente de la mitad del campo de visión del fondo: desde eye, el borde
+    // de la pantalla pasa por el borde de [-1, 1] en el plano de center
+    pub fn tan_half_fov(&self) -> f32 {
+        1.0 / (self.eye - self.center).norm()
+    }
+
+    // Gira eye alrededor de center: yaw sobre up y pitch sobre el eje derecho,
+    // sin pasar por encima de los polos
+    pub fn orbit(&mut self, yaw: f32, pitch: f32) {
+        let offset = self.eye - self.center;
+        let up = self.up.normalize();
+        let offset = glm::rotate_vec3(&offset, yaw, &up);
+
+        let right = offset.cross(&up).normalize();
+        let pitched = glm::rotate_vec3(&offset, pitch, &right);
+        let offset = if pitched.normalize().dot(&up).abs() < 0.99 { pitched } else { offset };
+
+        self.eye = self.center + offset;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn default_view_keeps_screen_xy_and_depth() {
+        let camera = Camera::new(Vec3::new(0.0, 0.0, 3.0), Vec3::zeros(), Vec3::y());
+        let view_projection = camera.view_projection();
+
+        // x, y del mundo son los de la pantalla y la profundidad va de 0
+        // (z = 2, delante) a 1 (z = -2, detrás)
+        for (world, expected) in [
+            (Vec3::new(0.5, -0.25, 0.0), [0.5, -0.25, 0.5]),
+            (Vec3::new(-1.0, 1.0, 2.0), [-1.0, 1.0, 0.0]),
+            (Vec3::new(0.0, 0.0, -2.0), [0.0, 0.0, 1.0]),
+        ] {
+            let clip = view_projection * glm::vec4(world.x, world.y, world.z, 1.0);
+            for (a, b) in clip.xyz().iter().zip(expected) {
+                assert!((a / clip.w - b).abs() < 1e-5, "{:?} -> {:?}", world, clip);
+            }
+        }
+        assert_eq!(camera.view_direction(), Vec3::z());
+    }
+
+    #[test]
+    fn orbit_keeps_the_distance_and_never_crosses_the_pole() {
+        let mut camera = Camera::new(Vec3::new(0.0, 0.0, 3.0), Vec3::zeros(), Vec3::y());
+        for _ in 0..100 {
+            camera.orbit(0.1, 0.1);
+            assert!(((camera.eye - camera.center).norm() - 3.0).abs() < 1e-4);
+            assert!(camera.view_direction().dot(&Vec3::y()).abs() < 0.99);
+        }
+    }
 }
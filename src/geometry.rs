@@ -0,0 +1,436 @@
+use nalgebra_glm::Vec3;
+use std::collections::HashMap;
+use std::f32::consts::PI;
+use std::io::{Error, ErrorKind};
+use crate::obj_loader::Obj;
+
+fn invalid_input(message: String) -> Error {
+    Error::new(ErrorKind::InvalidInput, message)
+}
+
+fn check_radius(name: &str, radius: f32) -> Result<(), Error> {
+    if radius.is_finite() && radius > 0.0 {
+        Ok(())
+    } else {
+        Err(invalid_input(format!("{} debe ser positivo (se pidió {})", name, radius)))
+    }
+}
+
+fn check_count(name: &str, value: u32, min: u32) -> Result<(), Error> {
+    if value >= min {
+        Ok(())
+    } else {
+        Err(invalid_input(format!("{} debe ser al menos {} (se pidió {})", name, min, value)))
+    }
+}
+
+// Tope para las mallas generadas: el máximo de un buffer de wgpu por defecto.
+// Se comprueba con los tamaños calculados a partir de los parámetros, antes
+// de generar nada, para que una resolución absurda sea un error y no una
+// reserva de varios GB
+pub const MAX_MESH_BYTES: u64 = 256 << 20;
+
+// Bytes de un vértice en la GPU (posición, normal y tangente)
+const VERTEX_BYTES: u64 = 40;
+
+fn check_mesh_size(vertex_count: u64, index_count: u64) -> Result<(), Error> {
+    let vertex_bytes = vertex_count.saturating_mul(VERTEX_BYTES);
+    let index_bytes = index_count.saturating_mul(4);
+    // Los índices son u32, así que ninguna malla puede pasar de ese rango
+    if vertex_count <= u32::MAX as u64 && vertex_bytes <= MAX_MESH_BYTES && index_bytes <= MAX_MESH_BYTES {
+        Ok(())
+    } else {
+        Err(invalid_input(format!(
+            "la malla tendría {} vértices ({} bytes) y {} índices ({} bytes), el máximo es {} bytes",
+            vertex_count, vertex_bytes, index_count, index_bytes, MAX_MESH_BYTES
+        )))
+    }
+}
+
+// Malla de esfera unitaria para los cuerpos sin modelo propio
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SphereMesh {
+    // Latitud/longitud con las mismas divisiones en ambos sentidos
+    Uv(u32),
+    // Nivel de subdivisión del icosaedro
+    Icosphere(u32),
+    // Divisiones por lado de cada cara del cubo
+    CubeSphere(u32),
+}
+
+impl Default for SphereMesh {
+    fn default() -> Self {
+        SphereMesh::Uv(50)
+    }
+}
+
+impl SphereMesh {
+    // "uv", "icosphere" o "cube-sphere", con la resolución opcional
+    pub fn parse(kind: &str, resolution: Option<u32>) -> Option<Self> {
+        match kind {
+            "uv" | "uv-sphere" => Some(SphereMesh::Uv(resolution.unwrap_or(50))),
+            "icosphere" => Some(SphereMesh::Icosphere(resolution.unwrap_or(5))),
+            "cube-sphere" => Some(SphereMesh::CubeSphere(resolution.unwrap_or(24))),
+            _ => None,
+        }
+    }
+
+    // La misma esfera con más o menos la mitad de detalle, para el LOD
+    pub fn coarser(&self) -> Option<Self> {
+        match *self {
+            SphereMesh::Uv(subdivisions) if subdivisions / 2 >= 6 => Some(SphereMesh::Uv(subdivisions / 2)),
+            SphereMesh::Icosphere(subdivisions) if subdivisions > 0 => Some(SphereMesh::Icosphere(subdivisions - 1)),
+            SphereMesh::CubeSphere(resolution) if resolution / 2 >= 2 => Some(SphereMesh::CubeSphere(resolution / 2)),
+            _ => None,
+        }
+    }
+
+    // Aristas aproximadas a lo largo del ecuador
+    pub fn equator_segments(&self) -> u32 {
+        match *self {
+            SphereMesh::Uv(subdivisions) => subdivisions,
+            SphereMesh::Icosphere(subdivisions) => 5 << subdivisions,
+            SphereMesh::CubeSphere(resolution) => 4 * resolution,
+        }
+    }
+
+    pub fn build(&self, radius: f32) -> Result<Obj, Error> {
+        match *self {
+            SphereMesh::Uv(subdivisions) => uv_sphere(radius, subdivisions, subdivisions),
+            SphereMesh::Icosphere(subdivisions) => icosphere(radius, subdivisions),
+            SphereMesh::CubeSphere(resolution) => cube_sphere(radius, resolution),
+        }
+    }
+}
+
+// Esfera de latitud/longitud. La costura y los polos repiten vértices para
+// que las coordenadas de textura no salten
+pub fn uv_sphere(radius: f32, rings: u32, sectors: u32) -> Result<Obj, Error> {
+    check_radius("radius", radius)?;
+    check_count("rings", rings, 2)?;
+    check_count("sectors", sectors, 3)?;
+    // Sin los triángulos degenerados de los polos
+    check_mesh_size(
+        (rings as u64 + 1) * (sectors as u64 + 1),
+        6 * (rings as u64 - 1) * sectors as u64,
+    )?;
+
+    let mut vertices = Vec::new();
+    let mut normals = Vec::new();
+    let mut tex_coords = Vec::new();
+    let mut indices = Vec::new();
+
+    for r in 0..=rings {
+        let theta = PI * r as f32 / rings as f32;
+        for s in 0..=sectors {
+            let phi = 2.0 * PI * s as f32 / sectors as f32;
+            let normal = Vec3::new(theta.sin() * phi.cos(), theta.cos(), theta.sin() * phi.sin());
+
+            vertices.push(normal * radius);
+            normals.push(normal);
+            tex_coords.push(Vec3::new(s as f32 / sectors as f32, r as f32 / rings as f32, 0.0));
+        }
+    }
+
+    for r in 0..rings {
+        for s in 0..sectors {
+            let first = r * (sectors + 1) + s;
+            let second = first + sectors + 1;
+
+            // Antihorario visto desde afuera; se omiten los triángulos degenerados de los polos
+            if r != 0 {
+                indices.extend_from_slice(&[first, first + 1, second]);
+            }
+            if r != rings - 1 {
+                indices.extend_from_slice(&[second, first + 1, second + 1]);
+            }
+        }
+    }
+
+    Ok(Obj::from_indexed(vertices, normals, tex_coords, &indices))
+}
+
+// Icosaedro subdividido: triángulos casi iguales en toda la superficie, sin
+// concentrarse en los polos. Cada subdivisión multiplica las caras por 4
+pub fn icosphere(radius: f32, subdivisions: u32) -> Result<Obj, Error> {
+    check_radius("radius", radius)?;
+    // 20·4^n caras y 10·4^n + 2 vértices; las copias de la costura son del
+    // orden de 2^n y no cambian el resultado
+    let faces = 1u64.checked_shl(2 * subdivisions).unwrap_or(u64::MAX).saturating_mul(20);
+    check_mesh_size(faces / 2 + 2, faces.saturating_mul(3))?;
+
+    let t = (1.0 + 5.0f32.sqrt()) / 2.0;
+    let mut vertices: Vec<Vec3> = [
+        [-1.0, t, 0.0], [1.0, t, 0.0], [-1.0, -t, 0.0], [1.0, -t, 0.0],
+        [0.0, -1.0, t], [0.0, 1.0, t], [0.0, -1.0, -t], [0.0, 1.0, -t],
+        [t, 0.0, -1.0], [t, 0.0, 1.0], [-t, 0.0, -1.0], [-t, 0.0, 1.0],
+    ]
+    .iter()
+    .map(|&p| Vec3::from(p).normalize())
+    .collect();
+
+    let mut faces: Vec<[u32; 3]> = vec![
+        [0, 11, 5], [0, 5, 1], [0, 1, 7], [0, 7, 10], [0, 10, 11],
+        [1, 5, 9], [5, 11, 4], [11, 10, 2], [10, 7, 6], [7, 1, 8],
+        [3, 9, 4], [3, 4, 2], [3, 2, 6], [3, 6, 8], [3, 8, 9],
+        [4, 9, 5], [2, 4, 11], [6, 2, 10], [8, 6, 7], [9, 8, 1],
+    ];
+
+    for _ in 0..subdivisions {
+        let mut midpoints: HashMap<(u32, u32), u32> = HashMap::new();
+        let mut midpoint = |a: u32, b: u32, vertices: &mut Vec<Vec3>| {
+            *midpoints.entry((a.min(b), a.max(b))).or_insert_with(|| {
+                vertices.push(((vertices[a as usize] + vertices[b as usize]) * 0.5).normalize());
+                (vertices.len() - 1) as u32
+            })
+        };
+
+        let mut next = Vec::with_capacity(faces.len() * 4);
+        for [a, b, c] in faces {
+            let ab = midpoint(a, b, &mut vertices);
+            let bc = midpoint(b, c, &mut vertices);
+            let ca = midpoint(c, a, &mut vertices);
+            next.extend_from_slice(&[[a, ab, ca], [b, bc, ab], [c, ca, bc], [ab, bc, ca]]);
+        }
+        faces = next;
+    }
+
+    Ok(spherical_mesh(radius, vertices, faces))
+}
+
+// Cubo subdividido y proyectado a la esfera. Usa la proyección que reparte
+// el área de forma casi uniforme en vez de solo normalizar
+pub fn cube_sphere(radius: f32, resolution: u32) -> Result<Obj, Error> {
+    check_radius("radius", radius)?;
+    check_count("resolution", resolution, 1)?;
+    check_mesh_size(
+        (resolution as u64 + 1).saturating_pow(2).saturating_mul(6),
+        (resolution as u64).saturating_pow(2).saturating_mul(36),
+    )?;
+
+    // (normal de la cara, eje u, eje v), con u x v = normal
+    let cube_faces = [
+        (Vec3::x(), -Vec3::z(), Vec3::y()),
+        (-Vec3::x(), Vec3::z(), Vec3::y()),
+        (Vec3::y(), Vec3::x(), -Vec3::z()),
+        (-Vec3::y(), Vec3::x(), Vec3::z()),
+        (Vec3::z(), Vec3::x(), Vec3::y()),
+        (-Vec3::z(), -Vec3::x(), Vec3::y()),
+    ];
+
+    let mut vertices = Vec::new();
+    let mut normals = Vec::new();
+    let mut tex_coords = Vec::new();
+    let mut indices = Vec::new();
+
+    for (normal, axis_u, axis_v) in cube_faces {
+        let base = vertices.len() as u32;
+
+        for j in 0..=resolution {
+            for i in 0..=resolution {
+                let u = i as f32 / resolution as f32;
+                let v = j as f32 / resolution as f32;
+                let p = normal + axis_u * (u * 2.0 - 1.0) + axis_v * (v * 2.0 - 1.0);
+
+                let (x2, y2, z2) = (p.x * p.x, p.y * p.y, p.z * p.z);
+                let on_sphere = Vec3::new(
+                    p.x * (1.0 - y2 / 2.0 - z2 / 2.0 + y2 * z2 / 3.0).sqrt(),
+                    p.y * (1.0 - z2 / 2.0 - x2 / 2.0 + z2 * x2 / 3.0).sqrt(),
+                    p.z * (1.0 - x2 / 2.0 - y2 / 2.0 + x2 * y2 / 3.0).sqrt(),
+                );
+
+                vertices.push(on_sphere * radius);
+                normals.push(on_sphere);
+                tex_coords.push(Vec3::new(u, v, 0.0));
+            }
+        }
+
+        let row = resolution + 1;
+        for j in 0..resolution {
+            for i in 0..resolution {
+                let a = base + j * row + i;
+                indices.extend_from_slice(&[a, a + 1, a + row + 1, a, a + row + 1, a + row]);
+            }
+        }
+    }
+
+    Ok(Obj::from_indexed(vertices, normals, tex_coords, &indices))
+}
+
+// Toro alrededor del eje y
+pub fn torus(major_radius: f32, minor_radius: f32, rings: u32, sides: u32) -> Result<Obj, Error> {
+    check_radius("radius", major_radius)?;
+    check_radius("inner-radius", minor_radius)?;
+    check_count("rings", rings, 3)?;
+    check_count("sides", sides, 3)?;
+    check_mesh_size((rings as u64 + 1) * (sides as u64 + 1), 6 * rings as u64 * sides as u64)?;
+
+    let mut vertices = Vec::new();
+    let mut normals = Vec::new();
+    let mut tex_coords = Vec::new();
+    let mut indices = Vec::new();
+
+    for r in 0..=rings {
+        let phi = 2.0 * PI * r as f32 / rings as f32;
+        let center = Vec3::new(phi.cos(), 0.0, -phi.sin()) * major_radius;
+
+        for s in 0..=sides {
+            let theta = 2.0 * PI * s as f32 / sides as f32;
+            let normal = Vec3::new(theta.cos() * phi.cos(), theta.sin(), -theta.cos() * phi.sin());
+
+            vertices.push(center + normal * minor_radius);
+            normals.push(normal);
+            tex_coords.push(Vec3::new(r as f32 / rings as f32, s as f32 / sides as f32, 0.0));
+        }
+    }
+
+    let row = sides + 1;
+    for r in 0..rings {
+        for s in 0..sides {
+            let a = r * row + s;
+            indices.extend_from_slice(&[a, a + row, a + row + 1, a, a + row + 1, a + 1]);
+        }
+    }
+
+    Ok(Obj::from_indexed(vertices, normals, tex_coords, &indices))
+}
+
+// Anillo plano en el plano xz, visible desde arriba y desde abajo (para los
+// anillos planetarios). u va del borde interior al exterior
+pub fn annulus(inner_radius: f32, outer_radius: f32, segments: u32) -> Result<Obj, Error> {
+    check_radius("inner-radius", inner_radius)?;
+    check_radius("radius", outer_radius)?;
+    check_count("segments", segments, 3)?;
+    check_mesh_size(4 * (segments as u64 + 1), 12 * segments as u64)?;
+    if inner_radius >= outer_radius {
+        return Err(invalid_input(format!(
+            "inner-radius ({}) debe ser menor que radius ({})",
+            inner_radius, outer_radius
+        )));
+    }
+
+    let mut vertices = Vec::new();
+    let mut normals = Vec::new();
+    let mut tex_coords = Vec::new();
+    let mut indices = Vec::new();
+
+    for (side, normal) in [Vec3::y(), -Vec3::y()].into_iter().enumerate() {
+        let base = vertices.len() as u32;
+
+        for s in 0..=segments {
+            let angle = 2.0 * PI * s as f32 / segments as f32;
+            let direction = Vec3::new(angle.cos(), 0.0, -angle.sin());
+            let v = s as f32 / segments as f32;
+
+            for (u, radius) in [(0.0, inner_radius), (1.0, outer_radius)] {
+                vertices.push(direction * radius);
+                normals.push(normal);
+                tex_coords.push(Vec3::new(u, v, 0.0));
+            }
+        }
+
+        for s in 0..segments {
+            let inner = base + s * 2;
+            let (a, b, c, d) = (inner, inner + 1, inner + 3, inner + 2);
+            if side == 0 {
+                indices.extend_from_slice(&[a, b, c, a, c, d]);
+            } else {
+                indices.extend_from_slice(&[a, c, b, a, d, c]);
+            }
+        }
+    }
+
+    Ok(Obj::from_indexed(vertices, normals, tex_coords, &indices))
+}
+
+// Plano cuadrado en xz centrado en el origen, mirando hacia +y
+pub fn plane(size: f32, resolution: u32) -> Result<Obj, Error> {
+    check_radius("size", size)?;
+    check_count("resolution", resolution, 1)?;
+    check_mesh_size(
+        (resolution as u64 + 1).saturating_pow(2),
+        (resolution as u64).saturating_pow(2).saturating_mul(6),
+    )?;
+
+    let mut vertices = Vec::new();
+    let mut normals = Vec::new();
+    let mut tex_coords = Vec::new();
+    let mut indices = Vec::new();
+
+    for j in 0..=resolution {
+        for i in 0..=resolution {
+            let u = i as f32 / resolution as f32;
+            let v = j as f32 / resolution as f32;
+
+            vertices.push(Vec3::new((u - 0.5) * size, 0.0, (v - 0.5) * size));
+            normals.push(Vec3::y());
+            tex_coords.push(Vec3::new(u, v, 0.0));
+        }
+    }
+
+    let row = resolution + 1;
+    for j in 0..resolution {
+        for i in 0..resolution {
+            let a = j * row + i;
+            indices.extend_from_slice(&[a, a + row, a + row + 1, a, a + row + 1, a + 1]);
+        }
+    }
+
+    Ok(Obj::from_indexed(vertices, normals, tex_coords, &indices))
+}
+
+// Coordenadas esféricas para una malla de vértices unitarios. Los triángulos
+// que cruzan la costura (u salta de 1 a 0) usan copias con u + 1
+fn spherical_mesh(radius: f32, unit_vertices: Vec<Vec3>, faces: Vec<[u32; 3]>) -> Obj {
+    let uv = |p: &Vec3| Vec3::new(0.5 + (-p.z).atan2(p.x) / (2.0 * PI), p.y.clamp(-1.0, 1.0).acos() / PI, 0.0);
+
+    let mut vertices: Vec<Vec3> = unit_vertices.iter().map(|p| p * radius).collect();
+    let mut normals = unit_vertices.clone();
+    let mut tex_coords: Vec<Vec3> = unit_vertices.iter().map(uv).collect();
+    let mut seam_copies: HashMap<u32, u32> = HashMap::new();
+    let mut indices = Vec::with_capacity(faces.len() * 3);
+
+    for face in faces {
+        let us = face.map(|i| tex_coords[i as usize].x);
+        let crosses_seam = us.iter().cloned().fold(f32::MIN, f32::max) - us.iter().cloned().fold(f32::MAX, f32::min) > 0.5;
+
+        for (corner, &i) in face.iter().enumerate() {
+            if crosses_seam && us[corner] < 0.5 {
+                let copy = *seam_copies.entry(i).or_insert_with(|| {
+                    vertices.push(vertices[i as usize]);
+                    normals.push(normals[i as usize]);
+                    tex_coords.push(tex_coords[i as usize] + Vec3::x());
+                    (vertices.len() - 1) as u32
+                });
+                indices.push(copy);
+            } else {
+                indices.push(i);
+            }
+        }
+    }
+
+    Obj::from_indexed(vertices, normals, tex_coords, &indices)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn oversized_meshes_are_rejected_before_generating() {
+        // Todas estas reservarían decenas de GB si se generaran
+        for result in [
+            uv_sphere(1.0, 65534, 65534),
+            icosphere(1.0, 20),
+            cube_sphere(1.0, u32::MAX),
+            torus(1.0, 0.5, 100_000, 100_000),
+            annulus(0.5, 1.0, u32::MAX),
+            plane(1.0, u32::MAX),
+        ] {
+            assert_eq!(result.err().map(|e| e.kind()), Some(ErrorKind::InvalidInput));
+        }
+
+        assert!(SphereMesh::default().build(1.0).is_ok());
+        assert!(icosphere(1.0, 5).is_ok());
+    }
+}
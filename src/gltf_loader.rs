@@ -0,0 +1,110 @@
+use nalgebra_glm::{Mat4, Vec3};
+use std::io::{Error, ErrorKind};
+use crate::obj_loader::Obj;
+
+// Material metallic-roughness de glTF. Solo se usan los factores: el
+// renderizador es procedural y no carga texturas
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PbrMaterial {
+    pub base_color: [f32; 4],
+    pub metallic: f32,
+    pub roughness: f32,
+    pub emissive: [f32; 3],
+}
+
+impl Default for PbrMaterial {
+    // Valores por defecto de la especificación glTF
+    fn default() -> Self {
+        PbrMaterial {
+            base_color: [1.0, 1.0, 1.0, 1.0],
+            metallic: 1.0,
+            roughness: 1.0,
+            emissive: [0.0, 0.0, 0.0],
+        }
+    }
+}
+
+// Una primitiva de un nodo, con la transformación acumulada desde la raíz
+pub struct GltfPrimitive {
+    pub mesh: Obj,
+    pub transform: Mat4,
+    pub material: PbrMaterial,
+}
+
+pub struct GltfModel {
+    pub primitives: Vec<GltfPrimitive>,
+}
+
+impl GltfModel {
+    pub fn load(filename: &str) -> Result<Self, Error> {
+        let (document, buffers, _images) = gltf::import(filename)
+            .map_err(|e| Error::new(ErrorKind::InvalidData, format!("{}: {}", filename, e)))?;
+
+        let scene = document
+            .default_scene()
+            .or_else(|| document.scenes().next())
+            .ok_or_else(|| Error::new(ErrorKind::InvalidData, format!("{}: no tiene escenas", filename)))?;
+
+        let mut primitives = Vec::new();
+        for node in scene.nodes() {
+            visit_node(&node, &Mat4::identity(), &buffers, &mut primitives);
+        }
+
+        Ok(GltfModel { primitives })
+    }
+}
+
+fn visit_node(
+    node: &gltf::Node,
+    parent_transform: &Mat4,
+    buffers: &[gltf::buffer::Data],
+    primitives: &mut Vec<GltfPrimitive>,
+) {
+    // glTF guarda las matrices por columnas, igual que nalgebra
+    let transform = parent_transform * Mat4::from(node.transform().matrix());
+
+    if let Some(mesh) = node.mesh() {
+        for primitive in mesh.primitives() {
+            if primitive.mode() != gltf::mesh::Mode::Triangles {
+                continue;
+            }
+
+            let reader = primitive.reader(|buffer| buffers.get(buffer.index()).map(|data| &data.0[..]));
+
+            let vertices: Vec<Vec3> = match reader.read_positions() {
+                Some(positions) => positions.map(Vec3::from).collect(),
+                None => continue,
+            };
+            let normals: Vec<Vec3> = reader
+                .read_normals()
+                .map(|normals| normals.map(Vec3::from).collect())
+                .unwrap_or_default();
+            let tex_coords: Vec<Vec3> = reader
+                .read_tex_coords(0)
+                .map(|uvs| uvs.into_f32().map(|[u, v]| Vec3::new(u, v, 0.0)).collect())
+                .unwrap_or_default();
+            let indices: Vec<u32> = match reader.read_indices() {
+                Some(indices) => indices.into_u32().collect(),
+                None => (0..vertices.len() as u32).collect(),
+            };
+
+            let pbr = primitive.material().pbr_metallic_roughness();
+            let material = PbrMaterial {
+                base_color: pbr.base_color_factor(),
+                metallic: pbr.metallic_factor(),
+                roughness: pbr.roughness_factor(),
+                emissive: primitive.material().emissive_factor(),
+            };
+
+            primitives.push(GltfPrimitive {
+                mesh: Obj::from_indexed(vertices, normals, tex_coords, &indices),
+                transform,
+                material,
+            });
+        }
+    }
+
+    for child in node.children() {
+        visit_node(&child, &transform, buffers, primitives);
+    }
+}
@@ -0,0 +1,96 @@
+use nalgebra_glm::{Mat3, Mat4, Vec3};
+use crate::ramp::ColorRamp;
+
+pub mod camera;
+// Renderizador por software: sólo lo usa el propio crate
+#[allow(dead_code)]
+pub(crate) mod color;
+#[allow(dead_code)]
+pub(crate) mod fragment;
+#[allow(dead_code)]
+pub(crate) mod framebuffer;
+pub mod geometry;
+pub mod gltf_loader;
+pub mod lod;
+pub mod noise;
+pub mod obj_loader;
+pub mod params;
+pub mod ply;
+pub mod ramp;
+pub mod scene;
+#[allow(dead_code)]
+pub(crate) mod shaders;
+pub mod sky;
+pub mod stl;
+pub mod tangents;
+pub mod vertex;
+
+// Uniforms del renderizador por software (shaders.rs); los de wgpu están en main.rs
+pub struct Uniforms {
+    // Del marco del cuerpo al mundo (Body::model_matrix) y la matriz que lleva
+    // sus normales al mundo: normal_matrix(&model_matrix), una vez por dibujo
+    pub model_matrix: Mat4,
+    pub normal_matrix: Mat4,
+    pub view_matrix: Mat4,
+    pub projection_matrix: Mat4,
+    pub viewport_matrix: Mat4,
+    pub time: u32,
+    // Amplitud del relieve de rocosos y lunas (fracción del radio)
+    pub displacement: f32,
+    // Semilla de todo el ruido del cuerpo
+    pub seed: u32,
+    // Rampas de color del cuerpo, en el orden de ramp_specs
+    pub ramps: Vec<ColorRamp>,
+    // Luz puntual del sol en el mundo (Scene::light)
+    pub light_position: Vec3,
+    pub light_intensity: f32,
+    pub sun_radius: f32,
+    // Otros cuerpos que pueden tapar al sol: centro y radio (Scene::occluders
+    // sin el cuerpo que se dibuja)
+    pub occluders: Vec<[f32; 4]>,
+    // Dirección unitaria hacia el observador, la misma en todo punto con una
+    // proyección ortográfica (view_dir en shader.wgsl)
+    pub view_dir: Vec3,
+}
+
+// Matriz que lleva normales con una transformación: la inversa transpuesta de
+// su parte 3x3, que las deja perpendiculares a la superficie aun con escalas no
+// uniformes. La luz se calcula en el mundo, así que es la de la matriz de
+// modelo, sin la vista
+pub fn normal_matrix(model: &Mat4) -> Mat4 {
+    let linear = nalgebra_glm::mat4_to_mat3(model);
+    let inverse_transpose = linear.try_inverse().map_or(Mat3::identity(), |inverse| inverse.transpose());
+    nalgebra_glm::mat3_to_mat4(&inverse_transpose)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use nalgebra_glm::{self as glm, Vec4};
+
+    #[test]
+    fn normal_matrix_keeps_normals_perpendicular_under_non_uniform_scale() {
+        let model = glm::translation(&Vec3::new(0.3, -0.2, 0.0))
+            * glm::rotation(0.7, &Vec3::new(1.0, 2.0, 0.5).normalize())
+            * glm::scaling(&Vec3::new(3.0, 0.5, 1.5));
+        let normals = normal_matrix(&model);
+
+        // Plano inclinado: normal y una tangente que está en el plano
+        let normal = Vec3::new(1.0, 1.0, 1.0).normalize();
+        let tangent = Vec3::new(1.0, -1.0, 0.0).normalize();
+
+        let transformed_normal = (normals * Vec4::new(normal.x, normal.y, normal.z, 0.0)).xyz();
+        let transformed_tangent = (model * Vec4::new(tangent.x, tangent.y, tangent.z, 0.0)).xyz();
+        assert!(transformed_normal.normalize().dot(&transformed_tangent.normalize()).abs() < 1e-5);
+
+        // Con la matriz de modelo tal cual la normal deja de ser perpendicular
+        let naive = (model * Vec4::new(normal.x, normal.y, normal.z, 0.0)).xyz();
+        assert!(naive.normalize().dot(&transformed_tangent.normalize()).abs() > 0.1);
+    }
+
+    #[test]
+    fn singular_matrix_falls_back_to_identity() {
+        let flat = glm::translation(&Vec3::new(1.0, 2.0, 3.0)) * glm::scaling(&Vec3::new(1.0, 0.0, 1.0));
+        assert_eq!(normal_matrix(&flat), Mat4::identity());
+    }
+}
@@ -0,0 +1,102 @@
+use crate::geometry::SphereMesh;
+use std::f32::consts::PI;
+
+// Se pasa al nivel siguiente cuando las aristas del ecuador del nivel actual
+// miden más que esto en pantalla
+const EDGE_PIXELS: f32 = 8.0;
+// Margen alrededor de cada umbral para no alternar entre dos niveles cuando
+// el radio queda justo en el límite
+const HYSTERESIS: f32 = 0.15;
+
+pub struct LodLevel {
+    pub sphere: SphereMesh,
+    // Radio en píxeles a partir del cual se usa este nivel
+    pub min_radius: f32,
+}
+
+// Cadena de resoluciones de una esfera, de menos a más detalle
+pub struct LodChain {
+    pub levels: Vec<LodLevel>,
+    current: usize,
+}
+
+impl LodChain {
+    // finest es el nivel más detallado; cada nivel anterior tiene la mitad de
+    // resolución, hasta count niveles o hasta que no se pueda reducir más
+    pub fn new(finest: SphereMesh, count: u32) -> Self {
+        let mut spheres = vec![finest];
+        while spheres.len() < count as usize {
+            match spheres[spheres.len() - 1].coarser() {
+                Some(sphere) => spheres.push(sphere),
+                None => break,
+            }
+        }
+        spheres.reverse();
+
+        let levels = spheres
+            .iter()
+            .enumerate()
+            .map(|(i, &sphere)| LodLevel {
+                sphere,
+                min_radius: match i {
+                    0 => 0.0,
+                    _ => spheres[i - 1].equator_segments() as f32 * EDGE_PIXELS / (2.0 * PI),
+                },
+            })
+            .collect();
+
+        LodChain { levels, current: 0 }
+    }
+
+    pub fn current(&self) -> usize {
+        self.current
+    }
+
+    // Sube o baja de nivel según el radio proyectado; solo cambia cuando se
+    // cruza el umbral más el margen
+    pub fn select(&mut self, screen_radius: f32) -> usize {
+        while self.current + 1 < self.levels.len()
+            && screen_radius > self.levels[self.current + 1].min_radius * (1.0 + HYSTERESIS)
+        {
+            self.current += 1;
+        }
+        while self.current > 0 && screen_radius < self.levels[self.current].min_radius * (1.0 - HYSTERESIS) {
+            self.current -= 1;
+        }
+        self.current
+    }
+}
+
+// Radio en píxeles de una esfera unitaria escalada por scale: la proyección
+// lleva [-1, 1] a todo el ancho y todo el alto de la ventana
+pub fn screen_radius(scale: f32, width: u32, height: u32) -> f32 {
+    scale * width.max(height) as f32 * 0.5
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn radius_around_a_threshold_does_not_flip_levels() {
+        let mut lod = LodChain::new(SphereMesh::default(), 5);
+        assert!(lod.levels.len() > 2);
+        let threshold = lod.levels[1].min_radius;
+
+        // Crece hasta pasar el umbral con margen: sube al nivel 1
+        assert_eq!(lod.select(threshold * (1.0 + 2.0 * HYSTERESIS)), 1);
+
+        // Oscila justo alrededor del umbral, dentro del margen: no cambia
+        for i in 0..100 {
+            let wobble = if i % 2 == 0 { 1.0 + HYSTERESIS * 0.9 } else { 1.0 - HYSTERESIS * 0.9 };
+            assert_eq!(lod.select(threshold * wobble), 1);
+        }
+
+        // Lo mismo desde abajo: después de bajar, la oscilación no lo sube
+        assert_eq!(lod.select(threshold * (1.0 - 2.0 * HYSTERESIS)), 0);
+        for i in 0..100 {
+            let wobble = if i % 2 == 0 { 1.0 + HYSTERESIS * 0.9 } else { 1.0 - HYSTERESIS * 0.9 };
+            assert_eq!(lod.select(threshold * wobble), 0);
+        }
+    }
+}
@@ -6,6 +6,18 @@ use winit::{
     window::Window,
 };
 use std::sync::Arc;
+use nalgebra_glm::{self as glm, Mat4};
+
+use lab5_shaders::camera::Camera;
+use lab5_shaders::geometry::SphereMesh;
+use lab5_shaders::gltf_loader::{GltfModel, PbrMaterial};
+use lab5_shaders::lod::{screen_radius, LodChain};
+use lab5_shaders::obj_loader::{NormalMode, Obj};
+use lab5_shaders::ramp::ShaderRamps;
+use lab5_shaders::scene::{Scene, Spin};
+use lab5_shaders::sky::StarCatalog;
+use lab5_shaders::{normal_matrix, ply, stl};
+use lab5_shaders::tangents::generate_tangents;
 
 #[repr(C)]
 #[derive(Copy, Clone, Debug, bytemuck::Pod, bytemuck::Zeroable)]
@@ -15,7 +27,76 @@ struct Uniforms {
     resolution: [f32; 2],
     planet_position: [f32; 2],
     planet_scale: f32,
-    _padding: f32,
+    // Amplitud del relieve de rocosos y lunas (fracción del radio)
+    displacement: f32,
+    // Transformación del nodo dentro del modelo (identidad salvo en glTF) y
+    // la que lleva sus normales (inversa transpuesta)
+    node_transform: [[f32; 4]; 4],
+    node_normal_matrix: [[f32; 4]; 4],
+    // Material metallic-roughness para el shader PBR (tipo 7)
+    base_color: [f32; 4],
+    emissive: [f32; 3],
+    metallic: f32,
+    roughness: f32,
+    // Semilla de todo el ruido del cuerpo
+    seed: u32,
+    _padding2: [f32; 2],
+    // Luz puntual del sol (Scene::light)
+    light_position: [f32; 3],
+    light_intensity: f32,
+    // Dirección hacia el observador (Camera::view_direction)
+    view_dir: [f32; 3],
+    // Espesor de la atmósfera (fracción del radio); 0 sin atmósfera
+    atmosphere: f32,
+    // Capa de nubes: altura (fracción del radio, 0 sin nubes), cobertura y
+    // velocidad de giro
+    cloud_altitude: f32,
+    cloud_cover: f32,
+    cloud_speed: f32,
+    // Posición del cuerpo en Occluders, para no taparse a sí mismo
+    body_index: u32,
+    // Del marco del cuerpo al mundo (Body::model_matrix) y su inversa
+    // transpuesta para las normales; cambian cada frame con el giro
+    model_matrix: [[f32; 4]; 4],
+    normal_matrix: [[f32; 4]; 4],
+    // Del mundo al espacio de recorte (Camera::view_projection), la misma
+    // cámara que la del fondo
+    view_projection: [[f32; 4]; 4],
+    // Ángulo girado por el cuerpo, para que las nubes giren respecto a él
+    spin_angle: f32,
+    _padding3: [f32; 3],
+}
+
+// Cuerpos que pueden eclipsar al sol (Scene::occluders); compartido por todos
+// los cuerpos. Los que pasen de MAX_OCCLUDERS no hacen sombra
+const MAX_OCCLUDERS: usize = 16;
+
+#[repr(C)]
+#[derive(Copy, Clone, Debug, bytemuck::Pod, bytemuck::Zeroable)]
+struct Occluders {
+    // xyz centro, w radio
+    spheres: [[f32; 4]; MAX_OCCLUDERS],
+    count: u32,
+    sun_radius: f32,
+    _padding: [f32; 2],
+}
+
+// Uniforms del fondo (Sky en shader.wgsl); se reescriben cada frame
+#[repr(C)]
+#[derive(Copy, Clone, Debug, bytemuck::Pod, bytemuck::Zeroable)]
+struct SkyUniforms {
+    // Orientación y encuadre de la cámara de los cuerpos (Camera::view_rotation
+    // y Camera::tan_half_fov)
+    rotation: [[f32; 4]; 4],
+    galactic_pole: [f32; 3],
+    tan_half_fov: f32,
+    galactic_center: [f32; 3],
+    _padding0: f32,
+    resolution: [f32; 2],
+    time: f32,
+    seed: u32,
+    star_count: u32,
+    _padding: [u32; 3],
 }
 
 #[repr(C)]
@@ -23,6 +104,7 @@ struct Uniforms {
 struct Vertex {
     position: [f32; 3],
     normal: [f32; 3],
+    tangent: [f32; 4],
 }
 
 impl Vertex {
@@ -41,13 +123,39 @@ impl Vertex {
                     shader_location: 1,
                     format: wgpu::VertexFormat::Float32x3,
                 },
+                wgpu::VertexAttribute {
+                    offset: std::mem::size_of::<[f32; 6]>() as wgpu::BufferAddress,
+                    shader_location: 2,
+                    format: wgpu::VertexFormat::Float32x4,
+                },
             ],
         }
     }
 }
 
-fn create_sphere(subdivisions: u32) -> (Vec<Vertex>, Vec<u16>) {
+fn create_sphere(subdivisions: u32, max_buffer_size: u64) -> Result<(Vec<Vertex>, Vec<u32>), std::io::Error> {
+    // Los tamaños salen de subdivisions: se comprueban contra el límite del
+    // dispositivo antes de generar nada
+    let vertex_count = (subdivisions as u64 + 1).saturating_pow(2);
+    let vertex_bytes = vertex_count.saturating_mul(std::mem::size_of::<Vertex>() as u64);
+    let index_bytes = (subdivisions as u64).saturating_pow(2).saturating_mul(6 * std::mem::size_of::<u32>() as u64);
+    if subdivisions < 3
+        || vertex_count > u32::MAX as u64
+        || vertex_bytes > max_buffer_size
+        || index_bytes > max_buffer_size
+    {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::InvalidInput,
+            format!(
+                "esfera con {} subdivisiones: deben ser al menos 3 y la malla ({} bytes de vértices, {} de índices) \
+                 no puede pasar de {} bytes por buffer",
+                subdivisions, vertex_bytes, index_bytes, max_buffer_size
+            ),
+        ));
+    }
+
     let mut vertices = Vec::new();
+    let mut tex_coords = Vec::new();
     let mut indices = Vec::new();
 
     for lat in 0..=subdivisions {
@@ -67,46 +175,332 @@ fn create_sphere(subdivisions: u32) -> (Vec<Vertex>, Vec<u16>) {
             vertices.push(Vertex {
                 position: [x, y, z],
                 normal: [x, y, z],
+                tangent: [0.0; 4],
             });
+            tex_coords.push([lon as f32 / subdivisions as f32, lat as f32 / subdivisions as f32]);
         }
     }
 
     for lat in 0..subdivisions {
         for lon in 0..subdivisions {
-            let first = (lat * (subdivisions + 1) + lon) as u16;
-            let second = first + subdivisions as u16 + 1;
+            let first = lat * (subdivisions + 1) + lon;
+            let second = first + subdivisions + 1;
 
+            // Antihorario visto desde afuera, igual que los OBJ
             indices.push(first);
-            indices.push(second);
             indices.push(first + 1);
+            indices.push(second);
 
             indices.push(second);
-            indices.push(second + 1);
             indices.push(first + 1);
+            indices.push(second + 1);
         }
     }
 
+    let positions: Vec<[f32; 3]> = vertices.iter().map(|v| v.position).collect();
+    let tangents = generate_tangents(&positions, &positions, &tex_coords, &indices);
+    for (vertex, tangent) in vertices.iter_mut().zip(tangents) {
+        vertex.tangent = tangent;
+    }
+
+    Ok((vertices, indices))
+}
+
+// Cuadrado en [-1, 1] en el plano xy, de frente a la cámara (CCW visto desde +z)
+fn create_quad() -> (Vec<Vertex>, Vec<u32>) {
+    let corners = [[-1.0, -1.0], [1.0, -1.0], [1.0, 1.0], [-1.0, 1.0]];
+    let vertices = corners
+        .iter()
+        .map(|&[x, y]| Vertex {
+            position: [x, y, 0.0],
+            normal: [0.0, 0.0, 1.0],
+            tangent: [1.0, 0.0, 0.0, 1.0],
+        })
+        .collect();
+    (vertices, vec![0, 1, 2, 0, 2, 3])
+}
+
+// Una parte de un modelo: el OBJ completo o una primitiva de un nodo glTF,
+// con la transformación del nodo dentro del modelo
+struct ModelPart {
+    vertices: Vec<Vertex>,
+    indices: Vec<u32>,
+    transform: Mat4,
+    material: PbrMaterial,
+}
+
+// De dónde sale la malla de un cuerpo
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum ModelSource<'a> {
+    File(&'a str, Option<NormalMode>),
+    Sphere(SphereMesh),
+}
+
+fn obj_to_vertices(obj: &Obj) -> (Vec<Vertex>, Vec<u32>) {
+    let (obj_vertices, indices) = obj.get_indexed_vertex_array();
+
+    let vertices = obj_vertices
+        .iter()
+        .map(|v| Vertex {
+            position: [v.position.x, v.position.y, v.position.z],
+            normal: [v.normal.x, v.normal.y, v.normal.z],
+            tangent: [v.tangent.x, v.tangent.y, v.tangent.z, v.tangent.w],
+        })
+        .collect();
+
     (vertices, indices)
 }
 
+fn load_model(source: ModelSource, max_buffer_size: u64) -> Result<Vec<ModelPart>, std::io::Error> {
+    let (filename, normals) = match source {
+        ModelSource::File(filename, normals) => (filename, normals),
+        ModelSource::Sphere(sphere) => {
+            let (vertices, indices) = match sphere {
+                SphereMesh::Uv(subdivisions) => create_sphere(subdivisions, max_buffer_size)?,
+                _ => obj_to_vertices(&sphere.build(1.0)?),
+            };
+            return Ok(vec![ModelPart {
+                vertices,
+                indices,
+                transform: Mat4::identity(),
+                material: PbrMaterial::default(),
+            }]);
+        }
+    };
+
+    let extension = std::path::Path::new(filename)
+        .extension()
+        .and_then(|e| e.to_str())
+        .unwrap_or("")
+        .to_lowercase();
+
+    let parts = match extension.as_str() {
+        "gltf" | "glb" => GltfModel::load(filename)?
+            .primitives
+            .into_iter()
+            .map(|p| (p.mesh, p.transform, p.material))
+            .collect(),
+        "ply" => vec![(ply::load(filename)?, Mat4::identity(), PbrMaterial::default())],
+        "stl" => vec![(stl::load(filename)?, Mat4::identity(), PbrMaterial::default())],
+        _ => vec![(Obj::load(filename)?, Mat4::identity(), PbrMaterial::default())],
+    };
+
+    // Normalizar al radio de la esfera unitaria para que "scale" signifique
+    // lo mismo en cualquier malla
+    let mut radius: f32 = 0.0;
+    let mut model = Vec::new();
+    for (mut obj, transform, material) in parts {
+        if let Some(mode) = normals {
+            obj.generate_normals(mode);
+        }
+        let (vertices, indices) = obj_to_vertices(&obj);
+
+        for v in &vertices {
+            radius = radius.max((transform * glm::vec4(v.position[0], v.position[1], v.position[2], 1.0)).xyz().norm());
+        }
+
+        model.push(ModelPart {
+            vertices,
+            indices,
+            transform,
+            material,
+        });
+    }
+
+    let radius = if radius > 0.0 { radius } else { 1.0 };
+    for part in &mut model {
+        part.transform = glm::scaling(&glm::vec3(1.0 / radius, 1.0 / radius, 1.0 / radius)) * part.transform;
+    }
+
+    Ok(model)
+}
+
+struct Mesh {
+    vertex_buffer: wgpu::Buffer,
+    index_buffer: wgpu::Buffer,
+    index_format: wgpu::IndexFormat,
+    num_indices: u32,
+}
+
+impl Mesh {
+    fn new(device: &wgpu::Device, label: &str, vertices: &[Vertex], indices: &[u32]) -> Result<Self, std::io::Error> {
+        // Las esferas generadas ya se miden antes de crearse, pero un archivo
+        // (OBJ, glTF, PLY, STL) puede pasar del límite de un buffer del
+        // dispositivo: se rechaza al cargar la escena en vez de dejar que wgpu
+        // entre en pánico
+        let max_buffer_size = device.limits().max_buffer_size;
+        let index_size = if vertices.len() <= u16::MAX as usize { 2 } else { 4 };
+        for (what, bytes) in [
+            ("vértices", std::mem::size_of_val(vertices) as u64),
+            ("índices", (indices.len() * index_size) as u64),
+        ] {
+            if bytes > max_buffer_size {
+                return Err(std::io::Error::new(
+                    std::io::ErrorKind::InvalidInput,
+                    format!(
+                        "malla '{}': {} bytes de {}, el dispositivo admite buffers de hasta {}",
+                        label, bytes, what, max_buffer_size
+                    ),
+                ));
+            }
+        }
+
+        let vertex_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some(&format!("Vertex Buffer ({})", label)),
+            contents: bytemuck::cast_slice(vertices),
+            usage: wgpu::BufferUsages::VERTEX,
+        });
+
+        // Uint16 ocupa la mitad cuando todos los vértices caben; si no, Uint32.
+        // 0xFFFF queda fuera porque es el índice de reinicio de primitivas
+        let narrow: Vec<u16>;
+        let (contents, index_format) = if index_size == 2 {
+            narrow = indices.iter().map(|&i| i as u16).collect();
+            (bytemuck::cast_slice(&narrow), wgpu::IndexFormat::Uint16)
+        } else {
+            (bytemuck::cast_slice(indices), wgpu::IndexFormat::Uint32)
+        };
+
+        let index_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some(&format!("Index Buffer ({})", label)),
+            contents,
+            usage: wgpu::BufferUsages::INDEX,
+        });
+
+        Ok(Self {
+            vertex_buffer,
+            index_buffer,
+            index_format,
+            num_indices: indices.len() as u32,
+        })
+    }
+}
+
+// Datos de dibujo de cada parte de cada cuerpo: su malla y sus uniforms
+struct BodyDraw {
+    // Índices en State::meshes, uno por nivel de detalle
+    meshes: Vec<usize>,
+    lod: Option<LodChain>,
+    // Sólo la primera parte de cada cuerpo dibuja sus nubes, su atmósfera y
+    // su corona (soles)
+    clouds: bool,
+    atmosphere: bool,
+    corona: bool,
+    // Inclinación y período de giro del cuerpo
+    spin: Spin,
+    uniforms: Uniforms,
+    uniform_buffer: wgpu::Buffer,
+    bind_group: wgpu::BindGroup,
+}
+
+const DEPTH_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Depth32Float;
+
+// Al empezar la cámara mira hacia -z desde el frente de la escena
+const CAMERA_POSITION: [f32; 3] = [0.0, 0.0, 3.0];
+// Cuánto gira la cámara alrededor de la escena con cada flecha
+const ORBIT_STEP: f32 = 0.05;
+
+// Triángulos CCW con back-face culling y prueba de profundidad; las capas
+// translúcidas no escriben profundidad
+fn create_pipeline(
+    device: &wgpu::Device,
+    layout: &wgpu::PipelineLayout,
+    shader: &wgpu::ShaderModule,
+    format: wgpu::TextureFormat,
+    (vertex_entry, fragment_entry): (&str, &str),
+    blend: wgpu::BlendState,
+    depth_write: bool,
+) -> wgpu::RenderPipeline {
+    device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+        label: Some(&format!("Pipeline ({})", fragment_entry)),
+        layout: Some(layout),
+        vertex: wgpu::VertexState {
+            module: shader,
+            entry_point: vertex_entry,
+            buffers: &[Vertex::desc()],
+        },
+        fragment: Some(wgpu::FragmentState {
+            module: shader,
+            entry_point: fragment_entry,
+            targets: &[Some(wgpu::ColorTargetState {
+                format,
+                blend: Some(blend),
+                write_mask: wgpu::ColorWrites::ALL,
+            })],
+        }),
+        primitive: wgpu::PrimitiveState {
+            topology: wgpu::PrimitiveTopology::TriangleList,
+            strip_index_format: None,
+            front_face: wgpu::FrontFace::Ccw,
+            cull_mode: Some(wgpu::Face::Back),
+            polygon_mode: wgpu::PolygonMode::Fill,
+            unclipped_depth: false,
+            conservative: false,
+        },
+        depth_stencil: Some(wgpu::DepthStencilState {
+            format: DEPTH_FORMAT,
+            depth_write_enabled: depth_write,
+            depth_compare: wgpu::CompareFunction::Less,
+            stencil: wgpu::StencilState::default(),
+            bias: wgpu::DepthBiasState::default(),
+        }),
+        multisample: wgpu::MultisampleState {
+            count: 1,
+            mask: !0,
+            alpha_to_coverage_enabled: false,
+        },
+        multiview: None,
+    })
+}
+
+fn create_depth_view(device: &wgpu::Device, config: &wgpu::SurfaceConfiguration) -> wgpu::TextureView {
+    let texture = device.create_texture(&wgpu::TextureDescriptor {
+        label: Some("Depth Texture"),
+        size: wgpu::Extent3d {
+            width: config.width,
+            height: config.height,
+            depth_or_array_layers: 1,
+        },
+        mip_level_count: 1,
+        sample_count: 1,
+        dimension: wgpu::TextureDimension::D2,
+        format: DEPTH_FORMAT,
+        usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
+        view_formats: &[],
+    });
+    texture.create_view(&wgpu::TextureViewDescriptor::default())
+}
+
 struct State {
     surface: wgpu::Surface<'static>,
     device: wgpu::Device,
     queue: wgpu::Queue,
     config: wgpu::SurfaceConfiguration,
     size: winit::dpi::PhysicalSize<u32>,
+    depth_view: wgpu::TextureView,
     render_pipeline: wgpu::RenderPipeline,
-    vertex_buffer: wgpu::Buffer,
-    index_buffer: wgpu::Buffer,
-    num_indices: u32,
-    uniform_buffer: wgpu::Buffer,
-    uniform_bind_group: wgpu::BindGroup,
-    uniforms: Uniforms,
+    clouds_pipeline: wgpu::RenderPipeline,
+    atmosphere_pipeline: wgpu::RenderPipeline,
+    // Esfera para las capas de nubes y atmósfera (vs_clouds y vs_atmosphere la
+    // escalan por cuerpo)
+    shell_mesh: Mesh,
+    corona_pipeline: wgpu::RenderPipeline,
+    // Cuadrado que vs_corona estira alrededor de cada sol; también cubre la
+    // pantalla para el cielo y es el punto de cada estrella
+    quad_mesh: Mesh,
+    sky_pipeline: wgpu::RenderPipeline,
+    stars_pipeline: wgpu::RenderPipeline,
+    sky_uniforms: SkyUniforms,
+    sky_buffer: wgpu::Buffer,
+    sky_bind_group: wgpu::BindGroup,
+    camera: Camera,
+    meshes: Vec<Mesh>,
+    bodies: Vec<BodyDraw>,
     start_time: std::time::Instant,
 }
 
 impl State {
-    async fn new(window: Arc<Window>) -> Self {
+    async fn new(window: Arc<Window>, scene: &Scene) -> Result<Self, std::io::Error> {
         let size = window.inner_size();
 
         let instance = wgpu::Instance::new(wgpu::InstanceDescriptor {
@@ -156,64 +550,234 @@ impl State {
             desired_maximum_frame_latency: 2,
         };
         surface.configure(&device, &config);
+        let depth_view = create_depth_view(&device, &config);
 
-        let (vertices, indices) = create_sphere(50);
-        let num_indices = indices.len() as u32;
-
-        let vertex_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
-            label: Some("Vertex Buffer"),
-            contents: bytemuck::cast_slice(&vertices),
-            usage: wgpu::BufferUsages::VERTEX,
-        });
-
-        let index_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
-            label: Some("Index Buffer"),
-            contents: bytemuck::cast_slice(&indices),
-            usage: wgpu::BufferUsages::INDEX,
-        });
-
-        let uniforms = Uniforms {
-            time: 0.0,
-            shader_type: 1,
-            resolution: [size.width as f32, size.height as f32],
-            planet_position: [0.0, 0.0],
-            planet_scale: 0.3,
-            _padding: 0.0,
+        // Mallas de la esfera de cada cuerpo (una por nivel de detalle) o de su
+        // modelo; cada una se crea una sola vez aunque la compartan varios cuerpos
+        let mut meshes = Vec::new();
+        let mut model_sources: Vec<ModelSource> = Vec::new();
+        let mut models: Vec<Vec<(usize, Mat4, PbrMaterial)>> = Vec::new();
+        let mut load_cached = |source, label: &str| -> Result<usize, std::io::Error> {
+            if let Some(i) = model_sources.iter().position(|&s| s == source) {
+                return Ok(i);
+            }
+            let mut parts = Vec::new();
+            for part in load_model(source, device.limits().max_buffer_size)? {
+                meshes.push(Mesh::new(&device, label, &part.vertices, &part.indices)?);
+                parts.push((meshes.len() - 1, part.transform, part.material));
+            }
+            models.push(parts);
+            model_sources.push(source);
+            Ok(models.len() - 1)
         };
 
-        let uniform_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
-            label: Some("Uniform Buffer"),
-            contents: bytemuck::cast_slice(&[uniforms]),
-            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
-        });
+        // Modelos de cada cuerpo: el de su archivo, o uno por nivel de detalle
+        // de su esfera junto con la cadena de LOD
+        let mut body_models = Vec::new();
+        for body in &scene.bodies {
+            body_models.push(match &body.mesh {
+                Some(path) => (vec![load_cached(ModelSource::File(path, body.normals), path)?], None),
+                None => {
+                    let lod = LodChain::new(body.sphere, body.lod_levels);
+                    let levels = lod
+                        .levels
+                        .iter()
+                        .map(|level| load_cached(ModelSource::Sphere(level.sphere), "esfera"))
+                        .collect::<Result<Vec<_>, _>>()?;
+                    (levels, Some(lod))
+                }
+            });
+        }
 
         let uniform_bind_group_layout =
             device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
-                entries: &[wgpu::BindGroupLayoutEntry {
-                    binding: 0,
-                    visibility: wgpu::ShaderStages::VERTEX | wgpu::ShaderStages::FRAGMENT,
-                    ty: wgpu::BindingType::Buffer {
-                        ty: wgpu::BufferBindingType::Uniform,
-                        has_dynamic_offset: false,
-                        min_binding_size: None,
+                entries: &[
+                    wgpu::BindGroupLayoutEntry {
+                        binding: 0,
+                        visibility: wgpu::ShaderStages::VERTEX | wgpu::ShaderStages::FRAGMENT,
+                        ty: wgpu::BindingType::Buffer {
+                            ty: wgpu::BufferBindingType::Uniform,
+                            has_dynamic_offset: false,
+                            min_binding_size: None,
+                        },
+                        count: None,
                     },
-                    count: None,
-                }],
+                    // Parámetros del shader del cuerpo (params.rs)
+                    wgpu::BindGroupLayoutEntry {
+                        binding: 1,
+                        visibility: wgpu::ShaderStages::VERTEX | wgpu::ShaderStages::FRAGMENT,
+                        ty: wgpu::BindingType::Buffer {
+                            ty: wgpu::BufferBindingType::Uniform,
+                            has_dynamic_offset: false,
+                            min_binding_size: None,
+                        },
+                        count: None,
+                    },
+                    // Rampas de color del cuerpo (ramp.rs)
+                    wgpu::BindGroupLayoutEntry {
+                        binding: 2,
+                        visibility: wgpu::ShaderStages::FRAGMENT,
+                        ty: wgpu::BindingType::Buffer {
+                            ty: wgpu::BufferBindingType::Uniform,
+                            has_dynamic_offset: false,
+                            min_binding_size: None,
+                        },
+                        count: None,
+                    },
+                    // Cuerpos que hacen sombra (eclipses)
+                    wgpu::BindGroupLayoutEntry {
+                        binding: 3,
+                        visibility: wgpu::ShaderStages::FRAGMENT,
+                        ty: wgpu::BindingType::Buffer {
+                            ty: wgpu::BufferBindingType::Uniform,
+                            has_dynamic_offset: false,
+                            min_binding_size: None,
+                        },
+                        count: None,
+                    },
+                ],
                 label: Some("uniform_bind_group_layout"),
             });
 
-        let uniform_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
-            layout: &uniform_bind_group_layout,
-            entries: &[wgpu::BindGroupEntry {
-                binding: 0,
-                resource: uniform_buffer.as_entire_binding(),
-            }],
-            label: Some("uniform_bind_group"),
+        let light = scene.light();
+        let mut occluders = Occluders {
+            spheres: [[0.0; 4]; MAX_OCCLUDERS],
+            count: 0,
+            sun_radius: light.radius,
+            _padding: [0.0; 2],
+        };
+        for (slot, sphere) in occluders.spheres.iter_mut().zip(scene.occluders()) {
+            *slot = sphere;
+            occluders.count += 1;
+        }
+        // Los cuerpos no se mueven: se sube una sola vez
+        let occluders_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Occluders Buffer"),
+            contents: bytemuck::cast_slice(&[occluders]),
+            usage: wgpu::BufferUsages::UNIFORM,
         });
 
+        // Una sola cámara para los cuerpos, sus capas y el fondo
+        let camera = Camera::new(
+            glm::Vec3::from(CAMERA_POSITION),
+            glm::Vec3::zeros(),
+            glm::Vec3::y(),
+        );
+
+        let mut bodies = Vec::new();
+        for (body_index, (body, (body_model, mut lod))) in scene.bodies.iter().zip(body_models).enumerate() {
+            // Cada parte lleva una malla por nivel de detalle
+            let parts: Vec<(Vec<usize>, Mat4, PbrMaterial)> = match &lod {
+                Some(_) => {
+                    let levels = body_model.iter().map(|&model| models[model][0].0).collect();
+                    vec![(levels, Mat4::identity(), PbrMaterial::default())]
+                }
+                None => models[body_model[0]]
+                    .iter()
+                    .map(|&(mesh, transform, material)| (vec![mesh], transform, material))
+                    .collect(),
+            };
+
+            let (params, _) = body
+                .shader_params()
+                .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, format!("{}: {}", body.name, e)))?;
+            // No cambian durante la ejecución: se suben una sola vez
+            let params_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label: Some(&format!("Params Buffer ({})", body.name)),
+                contents: bytemuck::cast_slice(&[params]),
+                usage: wgpu::BufferUsages::UNIFORM,
+            });
+
+            let ramps = body
+                .color_ramps()
+                .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, format!("{}: {}", body.name, e)))?;
+            let ramps_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label: Some(&format!("Ramps Buffer ({})", body.name)),
+                contents: bytemuck::cast_slice(&[ShaderRamps::new(&ramps)]),
+                usage: wgpu::BufferUsages::UNIFORM,
+            });
+
+            let mut has_clouds = body.clouds.is_some();
+            let mut has_atmosphere = body.atmosphere > 0.0;
+            let mut has_corona = body.shader_type == 1;
+            let clouds = body.clouds.unwrap_or_default();
+            for (meshes, transform, material) in parts {
+                let uniforms = Uniforms {
+                    time: 0.0,
+                    shader_type: body.shader_type,
+                    resolution: [size.width as f32, size.height as f32],
+                    planet_position: body.position,
+                    planet_scale: body.scale,
+                    displacement: body.displacement,
+                    node_transform: transform.into(),
+                    node_normal_matrix: normal_matrix(&transform).into(),
+                    base_color: material.base_color,
+                    emissive: material.emissive,
+                    metallic: material.metallic,
+                    roughness: material.roughness,
+                    seed: body.seed,
+                    _padding2: [0.0; 2],
+                    light_position: light.position,
+                    light_intensity: light.intensity,
+                    view_dir: camera.view_direction().into(),
+                    atmosphere: body.atmosphere,
+                    cloud_altitude: body.clouds.map_or(0.0, |clouds| clouds.altitude),
+                    cloud_cover: clouds.cover,
+                    cloud_speed: clouds.speed,
+                    body_index: body_index as u32,
+                    model_matrix: body.model_matrix(0.0).into(),
+                    normal_matrix: normal_matrix(&body.model_matrix(0.0)).into(),
+                    view_projection: camera.view_projection().into(),
+                    spin_angle: 0.0,
+                    _padding3: [0.0; 3],
+                };
+
+                let uniform_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                    label: Some(&format!("Uniform Buffer ({})", body.name)),
+                    contents: bytemuck::cast_slice(&[uniforms]),
+                    usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+                });
+
+                let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+                    layout: &uniform_bind_group_layout,
+                    entries: &[
+                        wgpu::BindGroupEntry {
+                            binding: 0,
+                            resource: uniform_buffer.as_entire_binding(),
+                        },
+                        wgpu::BindGroupEntry {
+                            binding: 1,
+                            resource: params_buffer.as_entire_binding(),
+                        },
+                        wgpu::BindGroupEntry {
+                            binding: 2,
+                            resource: ramps_buffer.as_entire_binding(),
+                        },
+                        wgpu::BindGroupEntry {
+                            binding: 3,
+                            resource: occluders_buffer.as_entire_binding(),
+                        },
+                    ],
+                    label: Some("uniform_bind_group"),
+                });
+
+                bodies.push(BodyDraw {
+                    meshes,
+                    lod: lod.take(),
+                    clouds: std::mem::take(&mut has_clouds),
+                    atmosphere: std::mem::take(&mut has_atmosphere),
+                    corona: std::mem::take(&mut has_corona),
+                    spin: body.spin,
+                    uniforms,
+                    uniform_buffer,
+                    bind_group,
+                });
+            }
+        }
+
         let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
             label: Some("Shader"),
-            source: wgpu::ShaderSource::Wgsl(include_str!("shader.wgsl").into()),
+            source: wgpu::ShaderSource::Wgsl(concat!(include_str!("noise.wgsl"), include_str!("shader.wgsl")).into()),
         });
 
         let render_pipeline_layout =
@@ -223,56 +787,185 @@ impl State {
                 push_constant_ranges: &[],
             });
 
-        let render_pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
-            label: Some("Render Pipeline"),
-            layout: Some(&render_pipeline_layout),
-            vertex: wgpu::VertexState {
-                module: &shader,
-                entry_point: "vs_main",
-                buffers: &[Vertex::desc()],
+        let render_pipeline = create_pipeline(
+            &device,
+            &render_pipeline_layout,
+            &shader,
+            config.format,
+            ("vs_main", "fs_main"),
+            wgpu::BlendState::REPLACE,
+            true,
+        );
+
+        // Capas translúcidas (nubes y atmósferas): se dibujan después de los
+        // cuerpos, sin escribir profundidad; color = propio + fondo * (1 - alpha)
+        let layer_blend = wgpu::BlendState {
+            color: wgpu::BlendComponent {
+                src_factor: wgpu::BlendFactor::One,
+                dst_factor: wgpu::BlendFactor::OneMinusSrcAlpha,
+                operation: wgpu::BlendOperation::Add,
             },
-            fragment: Some(wgpu::FragmentState {
-                module: &shader,
-                entry_point: "fs_main",
-                targets: &[Some(wgpu::ColorTargetState {
-                    format: config.format,
-                    blend: Some(wgpu::BlendState::REPLACE),
-                    write_mask: wgpu::ColorWrites::ALL,
-                })],
-            }),
-            primitive: wgpu::PrimitiveState {
-                topology: wgpu::PrimitiveTopology::TriangleList,
-                strip_index_format: None,
-                front_face: wgpu::FrontFace::Ccw,
-                cull_mode: Some(wgpu::Face::Back),
-                polygon_mode: wgpu::PolygonMode::Fill,
-                unclipped_depth: false,
-                conservative: false,
+            alpha: wgpu::BlendComponent::OVER,
+        };
+        let clouds_pipeline = create_pipeline(
+            &device,
+            &render_pipeline_layout,
+            &shader,
+            config.format,
+            ("vs_clouds", "fs_clouds"),
+            layer_blend,
+            false,
+        );
+        let atmosphere_pipeline = create_pipeline(
+            &device,
+            &render_pipeline_layout,
+            &shader,
+            config.format,
+            ("vs_atmosphere", "fs_atmosphere"),
+            layer_blend,
+            false,
+        );
+
+        // La corona y las estrellas son luz que se suma a lo que hay detrás
+        let additive_blend = wgpu::BlendState {
+            color: wgpu::BlendComponent {
+                src_factor: wgpu::BlendFactor::One,
+                dst_factor: wgpu::BlendFactor::One,
+                operation: wgpu::BlendOperation::Add,
             },
-            depth_stencil: None,
-            multisample: wgpu::MultisampleState {
-                count: 1,
-                mask: !0,
-                alpha_to_coverage_enabled: false,
+            alpha: wgpu::BlendComponent {
+                src_factor: wgpu::BlendFactor::Zero,
+                dst_factor: wgpu::BlendFactor::One,
+                operation: wgpu::BlendOperation::Add,
             },
-            multiview: None,
+        };
+        let corona_pipeline = create_pipeline(
+            &device,
+            &render_pipeline_layout,
+            &shader,
+            config.format,
+            ("vs_corona", "fs_corona"),
+            additive_blend,
+            false,
+        );
+
+        // Fondo: su propio bind group con el cielo (binding 4) y el catálogo
+        // de estrellas (binding 5)
+        let sky_bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 4,
+                    visibility: wgpu::ShaderStages::VERTEX | wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 5,
+                    visibility: wgpu::ShaderStages::VERTEX,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+            ],
+            label: Some("sky_bind_group_layout"),
+        });
+        let sky_pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Sky Pipeline Layout"),
+            bind_group_layouts: &[&sky_bind_group_layout],
+            push_constant_ranges: &[],
+        });
+        let sky_pipeline = create_pipeline(
+            &device,
+            &sky_pipeline_layout,
+            &shader,
+            config.format,
+            ("vs_sky", "fs_sky"),
+            wgpu::BlendState::REPLACE,
+            false,
+        );
+        let stars_pipeline = create_pipeline(
+            &device,
+            &sky_pipeline_layout,
+            &shader,
+            config.format,
+            ("vs_stars", "fs_stars"),
+            additive_blend,
+            false,
+        );
+
+        let catalog = StarCatalog::generate(&scene.sky);
+        let sky_uniforms = SkyUniforms {
+            rotation: camera.view_rotation().into(),
+            galactic_pole: catalog.galactic_pole.into(),
+            tan_half_fov: camera.tan_half_fov(),
+            galactic_center: catalog.galactic_center.into(),
+            _padding0: 0.0,
+            resolution: [size.width as f32, size.height as f32],
+            time: 0.0,
+            seed: scene.sky.seed,
+            star_count: catalog.stars.len() as u32,
+            _padding: [0; 3],
+        };
+        let sky_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Sky Buffer"),
+            contents: bytemuck::cast_slice(&[sky_uniforms]),
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
         });
+        let stars_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Stars Buffer"),
+            contents: bytemuck::cast_slice(&catalog.to_gpu()),
+            usage: wgpu::BufferUsages::UNIFORM,
+        });
+        let sky_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            layout: &sky_bind_group_layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 4,
+                    resource: sky_buffer.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 5,
+                    resource: stars_buffer.as_entire_binding(),
+                },
+            ],
+            label: Some("sky_bind_group"),
+        });
+
+        let (shell_vertices, shell_indices) = create_sphere(48, device.limits().max_buffer_size)?;
+        let shell_mesh = Mesh::new(&device, "capas", &shell_vertices, &shell_indices)?;
+        let (quad_vertices, quad_indices) = create_quad();
+        let quad_mesh = Mesh::new(&device, "corona", &quad_vertices, &quad_indices)?;
 
-        Self {
+        Ok(Self {
             surface,
             device,
             queue,
             config,
             size,
+            depth_view,
             render_pipeline,
-            vertex_buffer,
-            index_buffer,
-            num_indices,
-            uniform_buffer,
-            uniform_bind_group,
-            uniforms,
+            clouds_pipeline,
+            atmosphere_pipeline,
+            shell_mesh,
+            corona_pipeline,
+            quad_mesh,
+            sky_pipeline,
+            stars_pipeline,
+            sky_uniforms,
+            sky_buffer,
+            sky_bind_group,
+            camera,
+            meshes,
+            bodies,
             start_time: std::time::Instant::now(),
-        }
+        })
     }
 
     fn resize(&mut self, new_size: winit::dpi::PhysicalSize<u32>) {
@@ -281,21 +974,58 @@ impl State {
             self.config.width = new_size.width;
             self.config.height = new_size.height;
             self.surface.configure(&self.device, &self.config);
-            self.uniforms.resolution = [new_size.width as f32, new_size.height as f32];
+            self.depth_view = create_depth_view(&self.device, &self.config);
+            for body in &mut self.bodies {
+                body.uniforms.resolution = [new_size.width as f32, new_size.height as f32];
+            }
         }
     }
 
-    fn input(&mut self, _event: &KeyEvent) -> bool {
-        false
+    // Las flechas giran la cámara alrededor del centro de la escena
+    fn input(&mut self, event: &KeyEvent) -> bool {
+        if event.state != ElementState::Pressed {
+            return false;
+        }
+        let (yaw, pitch) = match event.physical_key {
+            PhysicalKey::Code(KeyCode::ArrowLeft) => (-ORBIT_STEP, 0.0),
+            PhysicalKey::Code(KeyCode::ArrowRight) => (ORBIT_STEP, 0.0),
+            PhysicalKey::Code(KeyCode::ArrowUp) => (0.0, ORBIT_STEP),
+            PhysicalKey::Code(KeyCode::ArrowDown) => (0.0, -ORBIT_STEP),
+            _ => return false,
+        };
+        self.camera.orbit(yaw, pitch);
+        true
     }
 
     fn update(&mut self) {
-        self.uniforms.time = self.start_time.elapsed().as_secs_f32();
-        self.queue.write_buffer(
-            &self.uniform_buffer,
-            0,
-            bytemuck::cast_slice(&[self.uniforms]),
-        );
+        let time = self.start_time.elapsed().as_secs_f32();
+
+        self.sky_uniforms.rotation = self.camera.view_rotation().into();
+        self.sky_uniforms.tan_half_fov = self.camera.tan_half_fov();
+        self.sky_uniforms.resolution = [self.size.width as f32, self.size.height as f32];
+        self.sky_uniforms.time = time;
+        self.queue.write_buffer(&self.sky_buffer, 0, bytemuck::cast_slice(&[self.sky_uniforms]));
+
+        let view_projection = self.camera.view_projection();
+        let view_dir = self.camera.view_direction();
+        for body in &mut self.bodies {
+            body.uniforms.time = time;
+            body.uniforms.view_projection = view_projection.into();
+            body.uniforms.view_dir = view_dir.into();
+            // Una vez por dibujo, no por vértice
+            let model = body.spin.model_matrix(body.uniforms.planet_position, body.uniforms.planet_scale, time);
+            body.uniforms.model_matrix = model.into();
+            body.uniforms.normal_matrix = normal_matrix(&model).into();
+            body.uniforms.spin_angle = body.spin.angle(time);
+            if let Some(lod) = &mut body.lod {
+                lod.select(screen_radius(body.uniforms.planet_scale, self.size.width, self.size.height));
+            }
+            self.queue.write_buffer(
+                &body.uniform_buffer,
+                0,
+                bytemuck::cast_slice(&[body.uniforms]),
+            );
+        }
     }
 
     fn render(&mut self) -> Result<(), wgpu::SurfaceError> {
@@ -310,41 +1040,6 @@ impl State {
                 label: Some("Render Encoder"),
             });
 
-        // Definir los planetas: [x, y, escala, shader_type]
-        let planets = [
-            [0.0, 0.0, 0.4, 1.0],    // Sol (centro, grande, amarillo)
-            [-0.6, 0.2, 0.15, 2.0],  // Planeta rocoso (izq arriba, pequeño, naranja/roca)
-            [-0.5, -0.3, 0.18, 5.0], // Planeta volcánico (izq abajo, mediano, rojo lava)
-            [0.5, 0.3, 0.3, 3.0],    // Gigante gaseoso (der arriba, grande, bandas)
-            [0.6, -0.2, 0.25, 4.0],  // Planeta con anillos (der abajo, marrón)
-            [0.2, -0.5, 0.12, 6.0],  // Luna/planeta helado (abajo centro, pequeño, gris)
-        ];
-
-        // Crear buffers y bind groups para cada planeta ANTES del render pass
-        let planet_data: Vec<_> = planets.iter().map(|planet| {
-            let mut uniforms = self.uniforms;
-            uniforms.planet_position = [planet[0], planet[1]];
-            uniforms.planet_scale = planet[2];
-            uniforms.shader_type = planet[3] as u32;
-
-            let uniform_buffer = self.device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
-                label: Some("Uniform Buffer"),
-                contents: bytemuck::cast_slice(&[uniforms]),
-                usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
-            });
-
-            let bind_group = self.device.create_bind_group(&wgpu::BindGroupDescriptor {
-                layout: &self.render_pipeline.get_bind_group_layout(0),
-                entries: &[wgpu::BindGroupEntry {
-                    binding: 0,
-                    resource: uniform_buffer.as_entire_binding(),
-                }],
-                label: Some("uniform_bind_group"),
-            });
-
-            (uniform_buffer, bind_group)
-        }).collect();
-
         {
             let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
                 label: Some("Render Pass"),
@@ -352,28 +1047,70 @@ impl State {
                     view: &view,
                     resolve_target: None,
                     ops: wgpu::Operations {
-                        load: wgpu::LoadOp::Clear(wgpu::Color {
-                            r: 0.05,
-                            g: 0.05,
-                            b: 0.15,
-                            a: 1.0,
-                        }),
+                        // El cielo cubre toda la pantalla
+                        load: wgpu::LoadOp::Clear(wgpu::Color::BLACK),
                         store: wgpu::StoreOp::Store,
                     },
                 })],
-                depth_stencil_attachment: None,
+                depth_stencil_attachment: Some(wgpu::RenderPassDepthStencilAttachment {
+                    view: &self.depth_view,
+                    depth_ops: Some(wgpu::Operations {
+                        load: wgpu::LoadOp::Clear(1.0),
+                        store: wgpu::StoreOp::Store,
+                    }),
+                    stencil_ops: None,
+                }),
                 occlusion_query_set: None,
                 timestamp_writes: None,
             });
 
+            // Fondo: Vía Láctea y nebulosas, y encima las estrellas
+            let quad = &self.quad_mesh;
+            render_pass.set_vertex_buffer(0, quad.vertex_buffer.slice(..));
+            render_pass.set_index_buffer(quad.index_buffer.slice(..), quad.index_format);
+            render_pass.set_bind_group(0, &self.sky_bind_group, &[]);
+            render_pass.set_pipeline(&self.sky_pipeline);
+            render_pass.draw_indexed(0..quad.num_indices, 0, 0..1);
+            render_pass.set_pipeline(&self.stars_pipeline);
+            render_pass.draw_indexed(0..quad.num_indices, 0, 0..self.sky_uniforms.star_count);
+
             render_pass.set_pipeline(&self.render_pipeline);
-            render_pass.set_vertex_buffer(0, self.vertex_buffer.slice(..));
-            render_pass.set_index_buffer(self.index_buffer.slice(..), wgpu::IndexFormat::Uint16);
 
-            // Dibujar cada planeta usando su bind group
-            for (_buffer, bind_group) in &planet_data {
-                render_pass.set_bind_group(0, bind_group, &[]);
-                render_pass.draw_indexed(0..self.num_indices, 0, 0..1);
+            // Dibujar cada planeta con su malla y su bind group
+            for body in &self.bodies {
+                let level = body.lod.as_ref().map_or(0, LodChain::current);
+                let mesh = &self.meshes[body.meshes[level]];
+                render_pass.set_vertex_buffer(0, mesh.vertex_buffer.slice(..));
+                render_pass.set_index_buffer(mesh.index_buffer.slice(..), mesh.index_format);
+                render_pass.set_bind_group(0, &body.bind_group, &[]);
+                render_pass.draw_indexed(0..mesh.num_indices, 0, 0..1);
+            }
+
+            // Nubes y atmósferas encima, con la profundidad de los cuerpos ya escrita
+            let shell = &self.shell_mesh;
+            render_pass.set_vertex_buffer(0, shell.vertex_buffer.slice(..));
+            render_pass.set_index_buffer(shell.index_buffer.slice(..), shell.index_format);
+
+            render_pass.set_pipeline(&self.clouds_pipeline);
+            for body in self.bodies.iter().filter(|body| body.clouds) {
+                render_pass.set_bind_group(0, &body.bind_group, &[]);
+                render_pass.draw_indexed(0..shell.num_indices, 0, 0..1);
+            }
+
+            render_pass.set_pipeline(&self.atmosphere_pipeline);
+            for body in self.bodies.iter().filter(|body| body.atmosphere) {
+                render_pass.set_bind_group(0, &body.bind_group, &[]);
+                render_pass.draw_indexed(0..shell.num_indices, 0, 0..1);
+            }
+
+            // Coronas de los soles
+            let quad = &self.quad_mesh;
+            render_pass.set_vertex_buffer(0, quad.vertex_buffer.slice(..));
+            render_pass.set_index_buffer(quad.index_buffer.slice(..), quad.index_format);
+            render_pass.set_pipeline(&self.corona_pipeline);
+            for body in self.bodies.iter().filter(|body| body.corona) {
+                render_pass.set_bind_group(0, &body.bind_group, &[]);
+                render_pass.draw_indexed(0..quad.num_indices, 0, 0..1);
             }
         }
 
@@ -384,9 +1121,51 @@ impl State {
     }
 }
 
+// Uso: lab5-shaders [escena] [--mesh <cuerpo>=<archivo.obj>]...
+fn load_scene() -> Result<Scene, String> {
+    let mut scene = None;
+    let mut mesh_overrides = Vec::new();
+
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        if arg == "--mesh" {
+            let value = args.next().ok_or("--mesh necesita <cuerpo>=<archivo.obj>")?;
+            let (body, path) = value
+                .split_once('=')
+                .ok_or_else(|| format!("--mesh {}: se esperaba <cuerpo>=<archivo.obj>", value))?;
+            mesh_overrides.push((body.to_string(), path.to_string()));
+        } else {
+            scene = Some(Scene::load(&arg).map_err(|e| format!("{}: {}", arg, e))?);
+        }
+    }
+
+    let mut scene = scene.unwrap_or_default();
+    for (body, path) in mesh_overrides {
+        scene
+            .body_mut(&body)
+            .ok_or_else(|| format!("--mesh: no hay ningún cuerpo llamado '{}'", body))?
+            .mesh = Some(path);
+    }
+
+    Ok(scene)
+}
+
 fn main() {
     env_logger::init();
-    
+
+    let scene = match load_scene() {
+        Ok(scene) => scene,
+        Err(e) => {
+            eprintln!("Error: {}", e);
+            std::process::exit(1);
+        }
+    };
+    for body in &scene.bodies {
+        for warning in body.shader_params().map(|(_, warnings)| warnings).unwrap_or_default() {
+            eprintln!("Aviso: cuerpo '{}': {}", body.name, warning);
+        }
+    }
+
     let event_loop = EventLoop::new().unwrap();
     let window = Arc::new(
         winit::window::WindowBuilder::new()
@@ -396,9 +1175,16 @@ fn main() {
             .unwrap(),
     );
 
-    let mut state = pollster::block_on(State::new(window.clone()));
+    let mut state = match pollster::block_on(State::new(window.clone(), &scene)) {
+        Ok(state) => state,
+        Err(e) => {
+            eprintln!("Error al cargar las mallas: {}", e);
+            std::process::exit(1);
+        }
+    };
 
-    println!("=== Sistema Solar - 6 Cuerpos Celestes ===");
+    println!("=== Sistema Solar - {} Cuerpos Celestes ===", scene.bodies.len());
+    println!("Flechas: Girar la cámara");
     println!("ESC: Salir");
 
     event_loop
@@ -0,0 +1,443 @@
+use nalgebra_glm::Vec3;
+
+// Ruido de gradiente en 3D. Es la misma implementación que noise.wgsl, con
+// un hash entero para que la CPU y la GPU den los mismos valores
+
+// Ruido de una semilla: cada cuerpo crea el suyo con la de sus uniforms, como
+// set_noise_seed en noise.wgsl
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Noise {
+    pub seed: u32,
+}
+
+impl Noise {
+    pub fn new(seed: u32) -> Self {
+        Noise { seed }
+    }
+
+    // Hash entero de una celda de la retícula, distinto para cada semilla
+    pub fn hash3(&self, x: i32, y: i32, z: i32) -> u32 {
+        let mut h = (x as u32).wrapping_mul(0x8da6b343)
+            ^ (y as u32).wrapping_mul(0xd8163841)
+            ^ (z as u32).wrapping_mul(0xcb1ab31f)
+            ^ self.seed.wrapping_mul(0x9e3779b9);
+        h = (h ^ (h >> 16)).wrapping_mul(0x7feb352d);
+        h = (h ^ (h >> 15)).wrapping_mul(0x846ca68b);
+        h ^ (h >> 16)
+    }
+
+    // Desplazamiento de la semilla para ruidos que no usan hash3, como los
+    // patrones con senos del renderizador por software; (0, 0, 0) con la semilla 0
+    pub fn seed_offset(&self) -> Vec3 {
+        if self.seed == 0 {
+            return Vec3::zeros();
+        }
+        let s = self.seed as i32;
+        Vec3::new(unit_float(self.hash3(s, 1, 2)), unit_float(self.hash3(1, s, 2)), unit_float(self.hash3(1, 2, s))) * 100.0
+    }
+
+    // Perlin mejorado, aproximadamente en [-1, 1]
+    pub fn perlin(&self, p: Vec3) -> f32 {
+        let (xi, yi, zi) = (p.x.floor() as i32, p.y.floor() as i32, p.z.floor() as i32);
+        let (x, y, z) = (p.x - p.x.floor(), p.y - p.y.floor(), p.z - p.z.floor());
+        let (u, v, w) = (fade(x), fade(y), fade(z));
+
+        let corner = |dx: i32, dy: i32, dz: i32| {
+            grad(self.hash3(xi + dx, yi + dy, zi + dz), x - dx as f32, y - dy as f32, z - dz as f32)
+        };
+
+        mix(
+            mix(mix(corner(0, 0, 0), corner(1, 0, 0), u), mix(corner(0, 1, 0), corner(1, 1, 0), u), v),
+            mix(mix(corner(0, 0, 1), corner(1, 0, 1), u), mix(corner(0, 1, 1), corner(1, 1, 1), u), v),
+            w,
+        )
+    }
+
+    // Simplex de Gustavson, aproximadamente en [-1, 1]. Sin ejes preferidos y más
+    // barato que Perlin por octava
+    pub fn simplex(&self, p: Vec3) -> f32 {
+        const F3: f32 = 1.0 / 3.0;
+        const G3: f32 = 1.0 / 6.0;
+
+        // Celda del simplex en el espacio sesgado
+        let s = (p.x + p.y + p.z) * F3;
+        let (i, j, k) = ((p.x + s).floor(), (p.y + s).floor(), (p.z + s).floor());
+        let t = (i + j + k) * G3;
+        let x0 = Vec3::new(p.x - (i - t), p.y - (j - t), p.z - (k - t));
+
+        // Orden de los ejes para elegir el tetraedro
+        let (i1, j1, k1, i2, j2, k2) = if x0.x >= x0.y {
+            if x0.y >= x0.z {
+                (1, 0, 0, 1, 1, 0)
+            } else if x0.x >= x0.z {
+                (1, 0, 0, 1, 0, 1)
+            } else {
+                (0, 0, 1, 1, 0, 1)
+            }
+        } else if x0.y < x0.z {
+            (0, 0, 1, 0, 1, 1)
+        } else if x0.x < x0.z {
+            (0, 1, 0, 0, 1, 1)
+        } else {
+            (0, 1, 0, 1, 1, 0)
+        };
+
+        let x1 = x0 - Vec3::new(i1 as f32, j1 as f32, k1 as f32) + Vec3::repeat(G3);
+        let x2 = x0 - Vec3::new(i2 as f32, j2 as f32, k2 as f32) + Vec3::repeat(2.0 * G3);
+        let x3 = x0 - Vec3::repeat(1.0) + Vec3::repeat(3.0 * G3);
+
+        let (ii, jj, kk) = (i as i32, j as i32, k as i32);
+        let contribution = |d: Vec3, di: i32, dj: i32, dk: i32| {
+            let falloff = 0.6 - d.dot(&d);
+            if falloff <= 0.0 {
+                0.0
+            } else {
+                let falloff = falloff * falloff;
+                falloff * falloff * grad(self.hash3(ii + di, jj + dj, kk + dk), d.x, d.y, d.z)
+            }
+        };
+
+        32.0 * (contribution(x0, 0, 0, 0)
+            + contribution(x1, i1, j1, k1)
+            + contribution(x2, i2, j2, k2)
+            + contribution(x3, 1, 1, 1))
+    }
+
+    // Movimiento browniano fraccional sobre Perlin, normalizado a [-1, 1]
+    pub fn fbm(&self, p: Vec3, octaves: u32) -> f32 {
+        let mut value = 0.0;
+        let mut amplitude = 0.5;
+        let mut total = 0.0;
+        let mut pos = p;
+
+        for _ in 0..octaves {
+            value += amplitude * self.perlin(pos);
+            total += amplitude;
+            pos = pos * 2.0 + octave_shift();
+            amplitude *= 0.5;
+        }
+
+        if total > 0.0 { value / total } else { 0.0 }
+    }
+
+    // Multifractal con crestas (Musgrave): cada octava pesa según la anterior, así
+    // los detalles se concentran en las crestas. En [0, 1]
+    pub fn ridged(&self, p: Vec3, octaves: u32) -> f32 {
+        let mut value = 0.0;
+        let mut amplitude = 0.5;
+        let mut total = 0.0;
+        let mut weight = 1.0;
+        let mut pos = p;
+
+        for _ in 0..octaves {
+            let signal = 1.0 - self.perlin(pos).abs();
+            let signal = signal * signal * weight;
+            weight = (signal * 2.0).clamp(0.0, 1.0);
+
+            value += amplitude * signal;
+            total += amplitude;
+            pos = pos * 2.0 + octave_shift();
+            amplitude *= 0.5;
+        }
+
+        if total > 0.0 { value / total } else { 0.0 }
+    }
+
+    // Suma de |Perlin| por octavas, en [0, 1]
+    pub fn turbulence(&self, p: Vec3, octaves: u32) -> f32 {
+        let mut value = 0.0;
+        let mut amplitude = 0.5;
+        let mut total = 0.0;
+        let mut pos = p;
+
+        for _ in 0..octaves {
+            value += amplitude * self.perlin(pos).abs();
+            total += amplitude;
+            pos = pos * 2.0 + octave_shift();
+            amplitude *= 0.5;
+        }
+
+        if total > 0.0 { value / total } else { 0.0 }
+    }
+
+    // Worley en 3D: un punto de rasgo por celda, buscando en las 27 celdas vecinas
+    pub fn worley(&self, p: Vec3) -> Cellular {
+        let (xi, yi, zi) = (p.x.floor() as i32, p.y.floor() as i32, p.z.floor() as i32);
+        let f = Vec3::new(p.x - p.x.floor(), p.y - p.y.floor(), p.z - p.z.floor());
+
+        let mut f1 = 8.0f32;
+        let mut f2 = 8.0f32;
+        let mut id = 0;
+
+        for dz in -1..=1 {
+            for dy in -1..=1 {
+                for dx in -1..=1 {
+                    let (cx, cy, cz) = (xi + dx, yi + dy, zi + dz);
+                    let cell_id = self.hash3(cx, cy, cz);
+                    let feature = Vec3::new(
+                        dx as f32 + unit_float(cell_id),
+                        dy as f32 + unit_float(self.hash3(cx + 7919, cy, cz)),
+                        dz as f32 + unit_float(self.hash3(cx, cy + 7919, cz)),
+                    );
+                    let diff = feature - f;
+                    let dist = diff.dot(&diff);
+
+                    if dist < f1 {
+                        f2 = f1;
+                        f1 = dist;
+                        id = cell_id;
+                    } else if dist < f2 {
+                        f2 = dist;
+                    }
+                }
+            }
+        }
+
+        let (f1, f2) = (f1.sqrt(), f2.sqrt());
+        Cellular { f1, f2, edge: f2 - f1, id }
+    }
+
+    // Desplaza el punto con tres fBm independientes; muestrear cualquier ruido en
+    // el resultado da formas arremolinadas en vez de manchas redondas
+    pub fn domain_warp(&self, p: Vec3, strength: f32) -> Vec3 {
+        let offset = Vec3::new(
+            self.fbm(p, 4),
+            self.fbm(p + Vec3::new(5.2, 1.3, 2.8), 4),
+            self.fbm(p + Vec3::new(1.7, 9.2, 3.4), 4),
+        );
+        p + offset * strength
+    }
+}
+
+// Producto con uno de los 12 gradientes de las aristas de un cubo (Perlin mejorado)
+fn grad(hash: u32, x: f32, y: f32, z: f32) -> f32 {
+    let h = hash & 15;
+    let u = if h < 8 { x } else { y };
+    let v = if h < 4 {
+        y
+    } else if h == 12 || h == 14 {
+        x
+    } else {
+        z
+    };
+    (if h & 1 == 0 { u } else { -u }) + (if h & 2 == 0 { v } else { -v })
+}
+
+fn fade(t: f32) -> f32 {
+    t * t * t * (t * (t * 6.0 - 15.0) + 10.0)
+}
+
+fn mix(a: f32, b: f32, t: f32) -> f32 {
+    a + (b - a) * t
+}
+
+// Desplazamiento entre octavas para que no coincidan los puntos de la
+// retícula, donde Perlin vale 0 en todas
+fn octave_shift() -> Vec3 {
+    Vec3::new(19.1, 7.7, 3.3)
+}
+
+// Resultado del ruido celular: distancias al punto de rasgo más cercano y al
+// segundo, y un id por celda
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Cellular {
+    pub f1: f32,
+    pub f2: f32,
+    // F2 - F1: cerca de 0 en los bordes entre celdas (grietas, placas)
+    pub edge: f32,
+    // Hash de la celda del punto más cercano, igual en toda la celda
+    pub id: u32,
+}
+
+// Los 24 bits altos del hash como flotante en [0, 1)
+pub fn unit_float(hash: u32) -> f32 {
+    (hash >> 8) as f32 / 16777216.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn seed_is_explicit_and_changes_the_pattern() {
+        let p = Vec3::new(1.3, -2.7, 0.4);
+        let (a, b) = (Noise::new(1), Noise::new(2));
+
+        // Sin estado compartido: intercalar semillas no cambia los valores
+        let first = a.perlin(p);
+        assert_ne!(b.perlin(p), first);
+        assert_eq!(a.perlin(p), first);
+
+        assert_eq!(Noise::default().seed_offset(), Vec3::zeros());
+        assert_ne!(a.seed_offset(), b.seed_offset());
+    }
+
+    // Puntos repartidos en [-10, 10]^3, los mismos en cada corrida
+    fn sample_points(count: i32) -> impl Iterator<Item = Vec3> {
+        let noise = Noise::new(99);
+        (0..count).map(move |i| {
+            let unit = Vec3::new(
+                unit_float(noise.hash3(i, 0, 0)),
+                unit_float(noise.hash3(i, 1, 0)),
+                unit_float(noise.hash3(i, 2, 0)),
+            );
+            unit * 20.0 - Vec3::repeat(10.0)
+        })
+    }
+
+    #[test]
+    fn output_stays_in_documented_range() {
+        for seed in [0, 1, 12345] {
+            let noise = Noise::new(seed);
+            for p in sample_points(2000) {
+                assert!(noise.perlin(p).abs() <= 1.0, "perlin {:?}", p);
+                assert!(noise.simplex(p).abs() <= 1.0, "simplex {:?}", p);
+                assert!(noise.fbm(p, 5).abs() <= 1.0, "fbm {:?}", p);
+                assert!((0.0..=1.0).contains(&noise.ridged(p, 5)), "ridged {:?}", p);
+                assert!((0.0..=1.0).contains(&noise.turbulence(p, 5)), "turbulence {:?}", p);
+
+                // El punto de la propia celda está a menos de la diagonal
+                let cell = noise.worley(p);
+                assert!(0.0 <= cell.f1 && cell.f1 <= cell.f2 && cell.f1 <= 3.0f32.sqrt(), "worley {:?}", p);
+                assert_eq!(cell.edge, cell.f2 - cell.f1);
+            }
+        }
+    }
+
+    #[test]
+    fn output_is_continuous() {
+        let noise = Noise::new(3);
+        let step = Vec3::new(1e-3, -1e-3, 1e-3);
+        for p in sample_points(2000) {
+            let q = p + step;
+            // Pendientes máximas medidas: Perlin ~2.5, simplex ~6, F1 1
+            assert!((noise.perlin(q) - noise.perlin(p)).abs() <= 10.0 * step.norm(), "perlin {:?}", p);
+            assert!((noise.simplex(q) - noise.simplex(p)).abs() <= 10.0 * step.norm(), "simplex {:?}", p);
+            assert!((noise.worley(q).f1 - noise.worley(p).f1).abs() <= 1.01 * step.norm(), "worley {:?}", p);
+        }
+
+        // Perlin vale 0 en los puntos de la retícula
+        assert_eq!(noise.perlin(Vec3::new(3.0, -2.0, 7.0)), 0.0);
+    }
+
+    // Valores de referencia con la semilla 7: (punto, perlin, simplex, F1, id).
+    // Los comprueban tanto noise.rs como noise.wgsl, así que si uno de los dos
+    // cambia sin el otro falla alguna de las dos pruebas
+    const GOLDEN_SEED: u32 = 7;
+    const GOLDEN: [([f32; 3], f32, f32, f32, u32); 4] = [
+        ([0.3, 0.7, 0.1], -0.1661083, -0.4729593, 0.7460243, 0x6c3eaa35),
+        ([1.3, -2.7, 0.4], -0.2291511, -0.1321974, 0.458182, 0x4d1a3f17),
+        ([-5.25, 3.5, 12.125], -0.2454169, 0.179566, 0.4912221, 0x7fbcb27e),
+        ([42.9, -17.3, -8.6], -0.4844554, -0.1860636, 0.6699165, 0x69c6700b),
+    ];
+
+    fn assert_golden(results: &[[f32; 3]], ids: &[u32], tolerance: f32) {
+        for (i, (point, perlin, simplex, f1, id)) in GOLDEN.iter().enumerate() {
+            for (got, expected) in results[i].iter().zip([perlin, simplex, f1]) {
+                assert!((got - expected).abs() <= tolerance, "{:?}: {:?}", point, results[i]);
+            }
+            assert_eq!(ids[i], *id, "{:?}", point);
+        }
+    }
+
+    #[test]
+    fn golden_values() {
+        let noise = Noise::new(GOLDEN_SEED);
+        let mut results = Vec::new();
+        let mut ids = Vec::new();
+        for (point, ..) in GOLDEN {
+            let p = Vec3::from(point);
+            let cell = noise.worley(p);
+            results.push([noise.perlin(p), noise.simplex(p), cell.f1]);
+            ids.push(cell.id);
+        }
+        assert_golden(&results, &ids, 1e-6);
+    }
+
+    // Corre noise.wgsl en un compute shader con los puntos de GOLDEN. Sin un
+    // adaptador (por ejemplo en CI sin GPU ni driver por software) se omite
+    #[test]
+    fn wgsl_port_matches_golden_values() {
+        let instance = wgpu::Instance::default();
+        let Some(adapter) = pollster::block_on(instance.request_adapter(&wgpu::RequestAdapterOptions::default())) else {
+            eprintln!("sin adaptador de wgpu: se omite la comparación con noise.wgsl");
+            return;
+        };
+        let (device, queue) = pollster::block_on(adapter.request_device(&wgpu::DeviceDescriptor::default(), None)).unwrap();
+
+        let source = format!(
+            "{}
+@group(0) @binding(0) var<storage, read_write> results: array<vec4<f32>, {count}>;
+
+@compute @workgroup_size(1)
+fn main() {{
+    set_noise_seed({seed}u);
+    var points = array<vec3<f32>, {count}>({points});
+    for (var i = 0; i < {count}; i++) {{
+        let cell = worley(points[i]);
+        results[i] = vec4<f32>(perlin(points[i]), simplex(points[i]), cell.f1, bitcast<f32>(cell.id));
+    }}
+}}
+",
+            include_str!("noise.wgsl"),
+            count = GOLDEN.len(),
+            seed = GOLDEN_SEED,
+            points = GOLDEN
+                .iter()
+                .map(|(p, ..)| format!("vec3<f32>({:?}, {:?}, {:?})", p[0], p[1], p[2]))
+                .collect::<Vec<_>>()
+                .join(", "),
+        );
+        let module = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("noise golden"),
+            source: wgpu::ShaderSource::Wgsl(source.into()),
+        });
+        let pipeline = device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+            label: None,
+            layout: None,
+            module: &module,
+            entry_point: "main",
+        });
+
+        let size = (GOLDEN.len() * 16) as u64;
+        let storage = device.create_buffer(&wgpu::BufferDescriptor {
+            label: None,
+            size,
+            usage: wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_SRC,
+            mapped_at_creation: false,
+        });
+        let readback = device.create_buffer(&wgpu::BufferDescriptor {
+            label: None,
+            size,
+            usage: wgpu::BufferUsages::MAP_READ | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: None,
+            layout: &pipeline.get_bind_group_layout(0),
+            entries: &[wgpu::BindGroupEntry {
+                binding: 0,
+                resource: storage.as_entire_binding(),
+            }],
+        });
+
+        let mut encoder = device.create_command_encoder(&wgpu::CommandEncoderDescriptor::default());
+        {
+            let mut pass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor::default());
+            pass.set_pipeline(&pipeline);
+            pass.set_bind_group(0, &bind_group, &[]);
+            pass.dispatch_workgroups(1, 1, 1);
+        }
+        encoder.copy_buffer_to_buffer(&storage, 0, &readback, 0, size);
+        queue.submit([encoder.finish()]);
+
+        readback.slice(..).map_async(wgpu::MapMode::Read, |result| result.unwrap());
+        device.poll(wgpu::Maintain::Wait);
+        let values: Vec<[f32; 4]> = bytemuck::cast_slice(&readback.slice(..).get_mapped_range()).to_vec();
+
+        let results: Vec<[f32; 3]> = values.iter().map(|v| [v[0], v[1], v[2]]).collect();
+        let ids: Vec<u32> = values.iter().map(|v| v[3].to_bits()).collect();
+        // La GPU puede fusionar operaciones (fma), así que no se pide igualdad exacta
+        assert_golden(&results, &ids, 1e-4);
+    }
+}
@@ -0,0 +1,243 @@
+// noise.wgsl - Ruido de gradiente en 3D. Es la misma implementación que
+// noise.rs, con un hash entero para que la CPU y la GPU den los mismos valores
+
+// Semilla del cuerpo que se está dibujando; vs_main y fs_main la fijan con
+// set_noise_seed antes de usar cualquier ruido
+var<private> noise_seed: u32 = 0u;
+// Desplazamiento de la semilla para ruidos que no usan hash3; (0, 0, 0) con
+// la semilla 0
+var<private> seed_offset: vec3<f32> = vec3<f32>(0.0);
+
+fn set_noise_seed(seed: u32) {
+    noise_seed = seed;
+    seed_offset = vec3<f32>(0.0);
+    if (seed != 0u) {
+        let s = bitcast<i32>(seed);
+        seed_offset = vec3<f32>(unit_float(hash3(s, 1, 2)), unit_float(hash3(1, s, 2)), unit_float(hash3(1, 2, s))) * 100.0;
+    }
+}
+
+// Hash entero de una celda de la retícula, distinto para cada semilla
+fn hash3(x: i32, y: i32, z: i32) -> u32 {
+    var h = (bitcast<u32>(x) * 0x8da6b343u) ^ (bitcast<u32>(y) * 0xd8163841u) ^ (bitcast<u32>(z) * 0xcb1ab31fu)
+        ^ (noise_seed * 0x9e3779b9u);
+    h = (h ^ (h >> 16u)) * 0x7feb352du;
+    h = (h ^ (h >> 15u)) * 0x846ca68bu;
+    return h ^ (h >> 16u);
+}
+
+// Producto con uno de los 12 gradientes de las aristas de un cubo (Perlin mejorado)
+fn grad(hash: u32, x: f32, y: f32, z: f32) -> f32 {
+    let h = hash & 15u;
+    let u = select(y, x, h < 8u);
+    var v = z;
+    if (h < 4u) {
+        v = y;
+    } else if (h == 12u || h == 14u) {
+        v = x;
+    }
+    return select(-u, u, (h & 1u) == 0u) + select(-v, v, (h & 2u) == 0u);
+}
+
+fn fade(t: f32) -> f32 {
+    return t * t * t * (t * (t * 6.0 - 15.0) + 10.0);
+}
+
+// Perlin mejorado, aproximadamente en [-1, 1]
+fn perlin(p: vec3<f32>) -> f32 {
+    let cell = vec3<i32>(floor(p));
+    let f = p - floor(p);
+    let u = fade(f.x);
+    let v = fade(f.y);
+    let w = fade(f.z);
+
+    let c000 = grad(hash3(cell.x, cell.y, cell.z), f.x, f.y, f.z);
+    let c100 = grad(hash3(cell.x + 1, cell.y, cell.z), f.x - 1.0, f.y, f.z);
+    let c010 = grad(hash3(cell.x, cell.y + 1, cell.z), f.x, f.y - 1.0, f.z);
+    let c110 = grad(hash3(cell.x + 1, cell.y + 1, cell.z), f.x - 1.0, f.y - 1.0, f.z);
+    let c001 = grad(hash3(cell.x, cell.y, cell.z + 1), f.x, f.y, f.z - 1.0);
+    let c101 = grad(hash3(cell.x + 1, cell.y, cell.z + 1), f.x - 1.0, f.y, f.z - 1.0);
+    let c011 = grad(hash3(cell.x, cell.y + 1, cell.z + 1), f.x, f.y - 1.0, f.z - 1.0);
+    let c111 = grad(hash3(cell.x + 1, cell.y + 1, cell.z + 1), f.x - 1.0, f.y - 1.0, f.z - 1.0);
+
+    return mix(
+        mix(mix(c000, c100, u), mix(c010, c110, u), v),
+        mix(mix(c001, c101, u), mix(c011, c111, u), v),
+        w
+    );
+}
+
+fn simplex_corner(d: vec3<f32>, cell: vec3<i32>) -> f32 {
+    let falloff = 0.6 - dot(d, d);
+    if (falloff <= 0.0) {
+        return 0.0;
+    }
+    let f2 = falloff * falloff;
+    return f2 * f2 * grad(hash3(cell.x, cell.y, cell.z), d.x, d.y, d.z);
+}
+
+// Simplex de Gustavson, aproximadamente en [-1, 1]. Sin ejes preferidos y más
+// barato que Perlin por octava
+fn simplex(p: vec3<f32>) -> f32 {
+    let F3 = 1.0 / 3.0;
+    let G3 = 1.0 / 6.0;
+
+    // Celda del simplex en el espacio sesgado
+    let s = (p.x + p.y + p.z) * F3;
+    let ijk = floor(p + vec3<f32>(s));
+    let t = (ijk.x + ijk.y + ijk.z) * G3;
+    let x0 = p - (ijk - vec3<f32>(t));
+
+    // Orden de los ejes para elegir el tetraedro
+    var o1: vec3<i32>;
+    var o2: vec3<i32>;
+    if (x0.x >= x0.y) {
+        if (x0.y >= x0.z) {
+            o1 = vec3<i32>(1, 0, 0); o2 = vec3<i32>(1, 1, 0);
+        } else if (x0.x >= x0.z) {
+            o1 = vec3<i32>(1, 0, 0); o2 = vec3<i32>(1, 0, 1);
+        } else {
+            o1 = vec3<i32>(0, 0, 1); o2 = vec3<i32>(1, 0, 1);
+        }
+    } else if (x0.y < x0.z) {
+        o1 = vec3<i32>(0, 0, 1); o2 = vec3<i32>(0, 1, 1);
+    } else if (x0.x < x0.z) {
+        o1 = vec3<i32>(0, 1, 0); o2 = vec3<i32>(0, 1, 1);
+    } else {
+        o1 = vec3<i32>(0, 1, 0); o2 = vec3<i32>(1, 1, 0);
+    }
+
+    let x1 = x0 - vec3<f32>(o1) + vec3<f32>(G3);
+    let x2 = x0 - vec3<f32>(o2) + vec3<f32>(2.0 * G3);
+    let x3 = x0 - vec3<f32>(1.0) + vec3<f32>(3.0 * G3);
+
+    let cell = vec3<i32>(ijk);
+    return 32.0 * (simplex_corner(x0, cell)
+        + simplex_corner(x1, cell + o1)
+        + simplex_corner(x2, cell + o2)
+        + simplex_corner(x3, cell + vec3<i32>(1, 1, 1)));
+}
+
+// Desplazamiento entre octavas para que no coincidan los puntos de la
+// retícula, donde Perlin vale 0 en todas
+const OCTAVE_SHIFT = vec3<f32>(19.1, 7.7, 3.3);
+
+// Movimiento browniano fraccional sobre Perlin, normalizado a [-1, 1]
+fn fbm(p: vec3<f32>, octaves: i32) -> f32 {
+    var value = 0.0;
+    var amplitude = 0.5;
+    var total = 0.0;
+    var pos = p;
+
+    for (var i = 0; i < octaves; i++) {
+        value += amplitude * perlin(pos);
+        total += amplitude;
+        pos = pos * 2.0 + OCTAVE_SHIFT;
+        amplitude *= 0.5;
+    }
+
+    return select(0.0, value / total, total > 0.0);
+}
+
+// Multifractal con crestas (Musgrave): cada octava pesa según la anterior, así
+// los detalles se concentran en las crestas. En [0, 1]
+fn ridged(p: vec3<f32>, octaves: i32) -> f32 {
+    var value = 0.0;
+    var amplitude = 0.5;
+    var total = 0.0;
+    var weight = 1.0;
+    var pos = p;
+
+    for (var i = 0; i < octaves; i++) {
+        var signal = 1.0 - abs(perlin(pos));
+        signal = signal * signal * weight;
+        weight = clamp(signal * 2.0, 0.0, 1.0);
+
+        value += amplitude * signal;
+        total += amplitude;
+        pos = pos * 2.0 + OCTAVE_SHIFT;
+        amplitude *= 0.5;
+    }
+
+    return select(0.0, value / total, total > 0.0);
+}
+
+// Suma de |Perlin| por octavas, en [0, 1]
+fn turbulence(p: vec3<f32>, octaves: i32) -> f32 {
+    var value = 0.0;
+    var amplitude = 0.5;
+    var total = 0.0;
+    var pos = p;
+
+    for (var i = 0; i < octaves; i++) {
+        value += amplitude * abs(perlin(pos));
+        total += amplitude;
+        pos = pos * 2.0 + OCTAVE_SHIFT;
+        amplitude *= 0.5;
+    }
+
+    return select(0.0, value / total, total > 0.0);
+}
+
+// Resultado del ruido celular: distancias al punto de rasgo más cercano y al
+// segundo, y un id por celda
+struct Cellular {
+    f1: f32,
+    f2: f32,
+    // F2 - F1: cerca de 0 en los bordes entre celdas (grietas, placas)
+    edge: f32,
+    // Hash de la celda del punto más cercano, igual en toda la celda
+    id: u32,
+}
+
+// Worley en 3D: un punto de rasgo por celda, buscando en las 27 celdas vecinas
+fn worley(p: vec3<f32>) -> Cellular {
+    let cell = vec3<i32>(floor(p));
+    let f = p - floor(p);
+
+    var f1 = 8.0;
+    var f2 = 8.0;
+    var id = 0u;
+
+    for (var dz = -1; dz <= 1; dz++) {
+        for (var dy = -1; dy <= 1; dy++) {
+            for (var dx = -1; dx <= 1; dx++) {
+                let c = cell + vec3<i32>(dx, dy, dz);
+                let cell_id = hash3(c.x, c.y, c.z);
+                let feature = vec3<f32>(
+                    f32(dx) + unit_float(cell_id),
+                    f32(dy) + unit_float(hash3(c.x + 7919, c.y, c.z)),
+                    f32(dz) + unit_float(hash3(c.x, c.y + 7919, c.z))
+                );
+                let diff = feature - f;
+                let dist = dot(diff, diff);
+
+                if (dist < f1) {
+                    f2 = f1;
+                    f1 = dist;
+                    id = cell_id;
+                } else if (dist < f2) {
+                    f2 = dist;
+                }
+            }
+        }
+    }
+
+    return Cellular(sqrt(f1), sqrt(f2), sqrt(f2) - sqrt(f1), id);
+}
+
+// Los 24 bits altos del hash como flotante en [0, 1)
+fn unit_float(hash: u32) -> f32 {
+    return f32(hash >> 8u) / 16777216.0;
+}
+
+// Desplaza el punto con tres fBm independientes; muestrear cualquier ruido en
+// el resultado da formas arremolinadas en vez de manchas redondas
+fn domain_warp(p: vec3<f32>, strength: f32) -> vec3<f32> {
+    let offset = vec3<f32>(
+        fbm(p, 4),
+        fbm(p + vec3<f32>(5.2, 1.3, 2.8), 4),
+        fbm(p + vec3<f32>(1.7, 9.2, 3.4), 4)
+    );
+    return p + offset * strength;
+}
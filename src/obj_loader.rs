@@ -1,8 +1,24 @@
 use nalgebra_glm::Vec3;
+use std::collections::HashMap;
 use std::fs::File;
-use std::io::{BufRead, BufReader};
+use std::io::{BufRead, BufReader, BufWriter, Write};
+use crate::tangents::generate_tangents;
 use crate::vertex::Vertex;
 
+// Ángulo de pliegue por defecto al generar normales suaves (grados)
+pub const DEFAULT_CREASE_ANGLE: f32 = 60.0;
+
+// Índice de normal para las caras que no traen "vn"
+const NO_NORMAL: usize = usize::MAX;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum NormalMode {
+    Flat,
+    // Promedio ponderado por ángulo; las caras que difieren más que el ángulo
+    // de pliegue (en grados) no se suavizan entre sí
+    Smooth(f32),
+}
+
 pub struct Obj {
     vertices: Vec<Vec3>,
     normals: Vec<Vec3>,
@@ -20,7 +36,7 @@ impl Obj {
         let mut tex_coords = Vec::new();
         let mut faces = Vec::new();
 
-        for line in reader.lines() {
+        for (number, line) in reader.lines().enumerate() {
             let line = line?;
             let parts: Vec<&str> = line.split_whitespace().collect();
 
@@ -29,76 +45,286 @@ impl Obj {
             }
 
             match parts[0] {
-                "v" => {
-                    if parts.len() >= 4 {
-                        let x: f32 = parts[1].parse().unwrap_or(0.0);
-                        let y: f32 = parts[2].parse().unwrap_or(0.0);
-                        let z: f32 = parts[3].parse().unwrap_or(0.0);
-                        vertices.push(Vec3::new(x, y, z));
-                    }
+                "v" if parts.len() >= 4 => {
+                    let x: f32 = parts[1].parse().unwrap_or(0.0);
+                    let y: f32 = parts[2].parse().unwrap_or(0.0);
+                    let z: f32 = parts[3].parse().unwrap_or(0.0);
+                    vertices.push(Vec3::new(x, y, z));
                 }
-                "vn" => {
-                    if parts.len() >= 4 {
-                        let x: f32 = parts[1].parse().unwrap_or(0.0);
-                        let y: f32 = parts[2].parse().unwrap_or(0.0);
-                        let z: f32 = parts[3].parse().unwrap_or(0.0);
-                        normals.push(Vec3::new(x, y, z));
-                    }
+                "vn" if parts.len() >= 4 => {
+                    let x: f32 = parts[1].parse().unwrap_or(0.0);
+                    let y: f32 = parts[2].parse().unwrap_or(0.0);
+                    let z: f32 = parts[3].parse().unwrap_or(0.0);
+                    normals.push(Vec3::new(x, y, z));
                 }
-                "vt" => {
-                    if parts.len() >= 3 {
-                        let u: f32 = parts[1].parse().unwrap_or(0.0);
-                        let v: f32 = parts[2].parse().unwrap_or(0.0);
-                        tex_coords.push(Vec3::new(u, v, 0.0));
-                    }
+                "vt" if parts.len() >= 3 => {
+                    let u: f32 = parts[1].parse().unwrap_or(0.0);
+                    let v: f32 = parts[2].parse().unwrap_or(0.0);
+                    tex_coords.push(Vec3::new(u, v, 0.0));
                 }
-                "f" => {
-                    if parts.len() >= 4 {
-                        let mut face = [0; 9];
-                        for (i, part) in parts.iter().skip(1).take(3).enumerate() {
-                            let indices: Vec<&str> = part.split('/').collect();
-                            if !indices.is_empty() {
-                                face[i * 3] = indices[0].parse::<usize>().unwrap_or(1) - 1;
-                            }
-                            if indices.len() > 1 && !indices[1].is_empty() {
-                                face[i * 3 + 1] = indices[1].parse::<usize>().unwrap_or(1) - 1;
-                            }
-                            if indices.len() > 2 {
-                                face[i * 3 + 2] = indices[2].parse::<usize>().unwrap_or(1) - 1;
-                            }
+                "f" if parts.len() >= 4 => {
+                    // Los índices de OBJ empiezan en 1: un 0 es un archivo roto
+                    let index = |text: &str| {
+                        text.parse::<usize>().unwrap_or(1).checked_sub(1).ok_or_else(|| {
+                            std::io::Error::new(
+                                std::io::ErrorKind::InvalidData,
+                                format!("{}:{}: índice 0 en la cara '{}'", filename, number + 1, line),
+                            )
+                        })
+                    };
+
+                    let mut face = [0, 0, NO_NORMAL, 0, 0, NO_NORMAL, 0, 0, NO_NORMAL];
+                    for (i, part) in parts.iter().skip(1).take(3).enumerate() {
+                        let indices: Vec<&str> = part.split('/').collect();
+                        if !indices.is_empty() {
+                            face[i * 3] = index(indices[0])?;
+                        }
+                        if indices.len() > 1 && !indices[1].is_empty() {
+                            face[i * 3 + 1] = index(indices[1])?;
+                        }
+                        if indices.len() > 2 && !indices[2].is_empty() {
+                            face[i * 3 + 2] = index(indices[2])?;
                         }
-                        faces.push(face);
                     }
+                    faces.push(face);
                 }
                 _ => {}
             }
         }
 
-        Ok(Obj {
+        let mut obj = Obj {
+            vertices,
+            normals,
+            tex_coords,
+            faces,
+        };
+
+        // Sin normales la iluminación no tiene sentido: generarlas
+        if !obj.has_normals() {
+            obj.generate_normals(NormalMode::Smooth(DEFAULT_CREASE_ANGLE));
+        }
+
+        Ok(obj)
+    }
+
+    // Malla ya indexada (un mismo índice para posición, normal y textura),
+    // como la de glTF o los generadores. Sin normales se generan igual que en load
+    pub fn from_indexed(vertices: Vec<Vec3>, normals: Vec<Vec3>, tex_coords: Vec<Vec3>, indices: &[u32]) -> Self {
+        let faces = indices
+            .chunks_exact(3)
+            .map(|t| {
+                let (a, b, c) = (t[0] as usize, t[1] as usize, t[2] as usize);
+                let normal = |i: usize| if normals.is_empty() { NO_NORMAL } else { i };
+                [a, a, normal(a), b, b, normal(b), c, c, normal(c)]
+            })
+            .collect();
+
+        let mut obj = Obj {
             vertices,
             normals,
             tex_coords,
             faces,
+        };
+
+        if !obj.has_normals() {
+            obj.generate_normals(NormalMode::Smooth(DEFAULT_CREASE_ANGLE));
+        }
+
+        obj
+    }
+
+    pub fn save(&self, filename: &str, write_tex_coords: bool) -> Result<(), std::io::Error> {
+        let mut file = BufWriter::new(File::create(filename)?);
+        let write_tex_coords = write_tex_coords && !self.tex_coords.is_empty();
+
+        writeln!(file, "# lab5-shaders")?;
+        writeln!(file, "# Vertices: {}", self.vertices.len())?;
+        writeln!(file, "# Faces: {}\n", self.faces.len())?;
+
+        for v in &self.vertices {
+            writeln!(file, "v {:.6} {:.6} {:.6}", v.x, v.y, v.z)?;
+        }
+        if write_tex_coords {
+            for t in &self.tex_coords {
+                writeln!(file, "vt {:.6} {:.6}", t.x, t.y)?;
+            }
+        }
+        for n in &self.normals {
+            writeln!(file, "vn {:.6} {:.6} {:.6}", n.x, n.y, n.z)?;
+        }
+
+        for face in &self.faces {
+            let corners: Vec<String> = (0..3)
+                .map(|i| {
+                    let (v, t, n) = (face[i * 3] + 1, face[i * 3 + 1] + 1, face[i * 3 + 2]);
+                    match (write_tex_coords, n < self.normals.len()) {
+                        (true, true) => format!("{}/{}/{}", v, t, n + 1),
+                        (true, false) => format!("{}/{}", v, t),
+                        (false, true) => format!("{}//{}", v, n + 1),
+                        (false, false) => format!("{}", v),
+                    }
+                })
+                .collect();
+            writeln!(file, "f {}", corners.join(" "))?;
+        }
+
+        file.flush()
+    }
+
+    pub fn has_normals(&self) -> bool {
+        self.faces
+            .iter()
+            .all(|face| (0..3).all(|i| face[i * 3 + 2] < self.normals.len()))
+    }
+
+    // Reemplaza las normales del archivo por normales calculadas
+    pub fn generate_normals(&mut self, mode: NormalMode) {
+        let face_normals: Vec<Vec3> = self.faces.iter().map(|face| self.face_normal(face)).collect();
+
+        self.normals.clear();
+        let mut seen: HashMap<(usize, [u32; 3]), usize> = HashMap::new();
+
+        match mode {
+            NormalMode::Flat => {
+                for (face, normal) in self.faces.iter_mut().zip(&face_normals) {
+                    for i in 0..3 {
+                        face[i * 3 + 2] = Self::push_normal(&mut self.normals, &mut seen, usize::MAX, *normal);
+                    }
+                }
+            }
+            NormalMode::Smooth(crease_angle) => {
+                let cos_crease = crease_angle.to_radians().cos();
+
+                // Caras que usan cada posición, con el ángulo de la cara en esa esquina
+                let mut incident: Vec<Vec<(usize, f32)>> = vec![Vec::new(); self.vertices.len()];
+                for (f, face) in self.faces.iter().enumerate() {
+                    for i in 0..3 {
+                        let pos_idx = face[i * 3];
+                        if pos_idx < incident.len() {
+                            incident[pos_idx].push((f, self.corner_angle(face, i)));
+                        }
+                    }
+                }
+
+                for f in 0..self.faces.len() {
+                    let own_normal = face_normals[f];
+
+                    for i in 0..3 {
+                        let pos_idx = self.faces[f][i * 3];
+                        let mut normal = Vec3::zeros();
+
+                        if let Some(faces) = incident.get(pos_idx) {
+                            for &(g, angle) in faces {
+                                if face_normals[g].dot(&own_normal) >= cos_crease {
+                                    normal += face_normals[g] * angle;
+                                }
+                            }
+                        }
+
+                        let normal = if normal.norm() > 1e-8 { normal.normalize() } else { own_normal };
+                        self.faces[f][i * 3 + 2] = Self::push_normal(&mut self.normals, &mut seen, pos_idx, normal);
+                    }
+                }
+            }
+        }
+    }
+
+    fn face_normal(&self, face: &[usize; 9]) -> Vec3 {
+        let a = self.vertices.get(face[0]).copied().unwrap_or(Vec3::zeros());
+        let b = self.vertices.get(face[3]).copied().unwrap_or(Vec3::zeros());
+        let c = self.vertices.get(face[6]).copied().unwrap_or(Vec3::zeros());
+
+        let normal = (b - a).cross(&(c - a));
+        if normal.norm() > 1e-12 {
+            normal.normalize()
+        } else {
+            Vec3::new(0.0, 1.0, 0.0)
+        }
+    }
+
+    fn corner_angle(&self, face: &[usize; 9], corner: usize) -> f32 {
+        let get = |i: usize| self.vertices.get(face[(i % 3) * 3]).copied().unwrap_or(Vec3::zeros());
+        let to_next = get(corner + 1) - get(corner);
+        let to_prev = get(corner + 2) - get(corner);
+
+        if to_next.norm() < 1e-12 || to_prev.norm() < 1e-12 {
+            return 0.0;
+        }
+        to_next.normalize().dot(&to_prev.normalize()).clamp(-1.0, 1.0).acos()
+    }
+
+    // Reutiliza la normal si esa posición ya tiene una idéntica
+    fn push_normal(
+        normals: &mut Vec<Vec3>,
+        seen: &mut HashMap<(usize, [u32; 3]), usize>,
+        pos_idx: usize,
+        normal: Vec3,
+    ) -> usize {
+        let key = (pos_idx, [normal.x.to_bits(), normal.y.to_bits(), normal.z.to_bits()]);
+        *seen.entry(key).or_insert_with(|| {
+            normals.push(normal);
+            normals.len() - 1
         })
     }
 
     pub fn get_vertex_array(&self) -> Vec<Vertex> {
+        let (vertex_array, indices) = self.get_indexed_vertex_array();
+        indices.iter().map(|&i| vertex_array[i as usize].clone()).collect()
+    }
+
+    // Igual que get_vertex_array pero comparte los vértices repetidos entre caras,
+    // para subirlos a la GPU con un index buffer
+    pub fn get_indexed_vertex_array(&self) -> (Vec<Vertex>, Vec<u32>) {
         let mut vertex_array = Vec::new();
+        let mut indices = Vec::with_capacity(self.faces.len() * 3);
+        let mut seen: HashMap<(usize, usize, usize), u32> = HashMap::new();
 
         for face in &self.faces {
             for i in 0..3 {
-                let pos_idx = face[i * 3];
-                let tex_idx = face[i * 3 + 1];
-                let norm_idx = face[i * 3 + 2];
+                let key = (face[i * 3], face[i * 3 + 1], face[i * 3 + 2]);
 
-                let position = self.vertices.get(pos_idx).copied().unwrap_or(Vec3::zeros());
-                let tex_coords = self.tex_coords.get(tex_idx).copied().unwrap_or(Vec3::zeros());
-                let normal = self.normals.get(norm_idx).copied().unwrap_or(Vec3::new(0.0, 1.0, 0.0));
+                let index = *seen.entry(key).or_insert_with(|| {
+                    let position = self.vertices.get(key.0).copied().unwrap_or(Vec3::zeros());
+                    let tex_coords = self.tex_coords.get(key.1).copied().unwrap_or(Vec3::zeros());
+                    let normal = self.normals.get(key.2).copied().unwrap_or(Vec3::new(0.0, 1.0, 0.0));
 
-                vertex_array.push(Vertex::new(position, normal, tex_coords));
+                    vertex_array.push(Vertex::new(position, normal, tex_coords));
+                    (vertex_array.len() - 1) as u32
+                });
+                indices.push(index);
             }
         }
 
-        vertex_array
+        let positions: Vec<[f32; 3]> = vertex_array.iter().map(|v| v.position.into()).collect();
+        let normals: Vec<[f32; 3]> = vertex_array.iter().map(|v| v.normal.into()).collect();
+        let tex_coords: Vec<[f32; 2]> = vertex_array.iter().map(|v| [v.tex_coords.x, v.tex_coords.y]).collect();
+        let tangents = generate_tangents(&positions, &normals, &tex_coords, &indices);
+
+        for (vertex, tangent) in vertex_array.iter_mut().zip(tangents) {
+            vertex.tangent = tangent.into();
+        }
+
+        (vertex_array, indices)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn zero_face_index_is_an_error() {
+        let path = std::env::temp_dir().join(format!("lab5-obj-{}.obj", std::process::id()));
+        let path = path.to_str().unwrap();
+
+        std::fs::write(path, "v 0 0 0\nv 1 0 0\nv 0 1 0\nf 1 2 3\n").unwrap();
+        assert_eq!(Obj::load(path).unwrap().get_vertex_array().len(), 3);
+
+        std::fs::write(path, "v 0 0 0\nv 1 0 0\nv 0 1 0\nf 0 2 3\n").unwrap();
+        let error = Obj::load(path).err().unwrap();
+        assert_eq!(error.kind(), std::io::ErrorKind::InvalidData);
+        assert!(error.to_string().contains(":4:"), "{}", error);
+
+        std::fs::remove_file(path).unwrap();
     }
 }
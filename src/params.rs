@@ -0,0 +1,299 @@
+// Parámetros ajustables de cada shader procedural. Cada uno ocupa un vec4 del
+// uniform ShaderParams; el orden de cada lista es el índice en shader.wgsl
+// (constantes ROCKY_*, GAS_*, ...), así que hay que mantenerlos a la par. Los
+// degradados de color van aparte, como rampas (ramp.rs)
+
+pub const MAX_PARAMS: usize = 16;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ParamValue {
+    Float(f32),
+    // RGB lineal en [0, 1]
+    Color([f32; 3]),
+    Vec3([f32; 3]),
+}
+
+impl ParamValue {
+    fn components(&self) -> usize {
+        match self {
+            ParamValue::Float(_) => 1,
+            ParamValue::Color(_) | ParamValue::Vec3(_) => 3,
+        }
+    }
+
+    fn to_vec4(self) -> [f32; 4] {
+        match self {
+            ParamValue::Float(v) => [v, 0.0, 0.0, 0.0],
+            ParamValue::Color([r, g, b]) => [r, g, b, 0.0],
+            ParamValue::Vec3([x, y, z]) => [x, y, z, 0.0],
+        }
+    }
+
+    // El mismo tipo con los valores leídos de la escena
+    fn with_values(&self, values: &[f32]) -> Option<Self> {
+        if values.len() != self.components() {
+            return None;
+        }
+        Some(match self {
+            ParamValue::Float(_) => ParamValue::Float(values[0]),
+            ParamValue::Color(_) => ParamValue::Color([values[0], values[1], values[2]]),
+            ParamValue::Vec3(_) => ParamValue::Vec3([values[0], values[1], values[2]]),
+        })
+    }
+}
+
+pub struct ParamSpec {
+    pub name: &'static str,
+    pub default: ParamValue,
+}
+
+const fn float(name: &'static str, value: f32) -> ParamSpec {
+    ParamSpec { name, default: ParamValue::Float(value) }
+}
+
+const fn color(name: &'static str, r: f32, g: f32, b: f32) -> ParamSpec {
+    ParamSpec { name, default: ParamValue::Color([r, g, b]) }
+}
+
+const fn vec3(name: &'static str, x: f32, y: f32, z: f32) -> ParamSpec {
+    ParamSpec { name, default: ParamValue::Vec3([x, y, z]) }
+}
+
+const SUN_PARAMS: &[ParamSpec] = &[
+    float("frequency", 3.0),
+    float("speed", 1.0),
+    float("swirl_arms", 6.0),
+    float("spot_threshold", 0.68),
+    // Brillo de la corona y su radio exterior, en radios del sol
+    float("corona", 0.8),
+    float("corona_size", 3.0),
+    // Cuánto se oscurece el borde del disco (0 = nada)
+    float("limb_darkening", 0.6),
+];
+
+const ROCKY_PARAMS: &[ParamSpec] = &[
+    float("frequency", 5.0),
+    float("ocean_level", 0.35),
+    float("mountain_level", 0.55),
+    float("snow_level", 0.65),
+    color("snow_color", 0.98, 0.98, 1.0),
+    color("mountain_color", 0.51, 0.43, 0.35),
+    // Rugosidad GGX: el agua lisa muestra el reflejo del sol, la tierra no
+    float("ocean_roughness", 0.15),
+    float("land_roughness", 0.8),
+    // Luces de ciudades del lado de noche: población mínima y color
+    float("city_level", 0.55),
+    color("city_color", 1.0, 0.78, 0.42),
+];
+
+const GAS_PARAMS: &[ParamSpec] = &[
+    float("frequency", 3.5),
+    float("band_frequency", 18.0),
+    float("turbulence", 1.0),
+    float("speed", 1.0),
+    vec3("spot_center", 0.6, -0.3, 0.0),
+    float("spot_radius", 0.5),
+    color("spot_color", 0.94, 0.39, 0.27),
+    color("spot_dark_color", 0.75, 0.24, 0.16),
+];
+
+const RINGED_PARAMS: &[ParamSpec] = &[
+    float("frequency", 3.0),
+    float("band_frequency", 20.0),
+    float("ring_inner", 0.75),
+    float("ring_outer", 2.0),
+    float("ring_thickness", 0.18),
+    color("color1", 1.0, 0.94, 0.82),
+    color("color2", 0.94, 0.86, 0.69),
+];
+
+const VOLCANIC_PARAMS: &[ParamSpec] = &[
+    float("frequency", 4.0),
+    float("crack_width", 0.12),
+    float("speed", 1.0),
+    float("pulse_speed", 5.0),
+    color("glow_color", 1.0, 0.86, 0.39),
+];
+
+const MOON_PARAMS: &[ParamSpec] = &[
+    float("frequency", 5.0),
+    float("crater_size", 0.25),
+    float("mare_level", 0.3),
+    color("crater_color", 0.24, 0.24, 0.24),
+    color("mare_color", 0.31, 0.31, 0.31),
+];
+
+// Parámetros que se quitaron de un shader: las escenas que todavía los usan
+// cargan igual y el valor se ignora con un aviso que dice qué usar en su lugar
+pub struct RetiredParam {
+    pub name: &'static str,
+    pub replacement: &'static str,
+}
+
+const fn retired(name: &'static str, replacement: &'static str) -> RetiredParam {
+    RetiredParam { name, replacement }
+}
+
+// Los colores fijos pasaron a rampas (ramp.rs) y el halo a la capa de
+// atmósfera
+const SUN_RETIRED: &[RetiredParam] = &[
+    retired("hot_color", "ramp temperature"),
+    retired("warm_color", "ramp temperature"),
+    retired("mid_color", "ramp temperature"),
+    retired("cool_color", "ramp temperature"),
+];
+
+const ROCKY_RETIRED: &[RetiredParam] = &[
+    retired("deep_ocean_color", "ramp ocean"),
+    retired("ocean_color", "ramp ocean"),
+    retired("forest_color", "ramp vegetation"),
+    retired("grass_color", "ramp vegetation"),
+    retired("desert_color", "ramp vegetation"),
+    // El color de la atmósfera sale de la dispersión de Rayleigh y Mie
+    retired("atmosphere_color", "atmosphere <grosor>"),
+];
+
+const GAS_RETIRED: &[RetiredParam] = &[
+    retired("color1", "ramp bands"),
+    retired("color2", "ramp bands"),
+    retired("color3", "ramp bands"),
+    retired("color4", "ramp bands"),
+];
+
+const RINGED_RETIRED: &[RetiredParam] = &[
+    retired("ring_light_color", "ramp rings"),
+    retired("ring_mid_color", "ramp rings"),
+    retired("ring_dark_color", "ramp rings"),
+];
+
+const VOLCANIC_RETIRED: &[RetiredParam] = &[
+    retired("rock_color", "ramp rock"),
+    retired("dark_rock_color", "ramp rock"),
+    retired("lava_white_color", "ramp lava"),
+    retired("lava_yellow_color", "ramp lava"),
+    retired("lava_orange_color", "ramp lava"),
+    retired("lava_red_color", "ramp lava"),
+];
+
+const MOON_RETIRED: &[RetiredParam] = &[
+    retired("highland_level", "ramp highlands"),
+    retired("highland_color", "ramp highlands"),
+    retired("base_color", "ramp highlands"),
+];
+
+pub fn retired_params(shader_type: u32) -> &'static [RetiredParam] {
+    match shader_type {
+        1 => SUN_RETIRED,
+        2 => ROCKY_RETIRED,
+        3 => GAS_RETIRED,
+        4 => RINGED_RETIRED,
+        5 => VOLCANIC_RETIRED,
+        6 => MOON_RETIRED,
+        _ => &[],
+    }
+}
+
+// Parámetros que declara cada tipo de shader (vacío para el PBR, que usa el
+// material del modelo)
+pub fn param_specs(shader_type: u32) -> &'static [ParamSpec] {
+    match shader_type {
+        1 => SUN_PARAMS,
+        2 => ROCKY_PARAMS,
+        3 => GAS_PARAMS,
+        4 => RINGED_PARAMS,
+        5 => VOLCANIC_PARAMS,
+        6 => MOON_PARAMS,
+        _ => &[],
+    }
+}
+
+// Bloque que se sube como uniform (binding 1)
+#[repr(C)]
+#[derive(Debug, Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct ShaderParams {
+    pub values: [[f32; 4]; MAX_PARAMS],
+}
+
+impl ShaderParams {
+    pub fn defaults(shader_type: u32) -> Self {
+        let mut params = ShaderParams { values: [[0.0; 4]; MAX_PARAMS] };
+        for (slot, spec) in params.values.iter_mut().zip(param_specs(shader_type)) {
+            *slot = spec.default.to_vec4();
+        }
+        params
+    }
+
+    // Reemplaza un parámetro por nombre; el error explica qué se esperaba. Un
+    // parámetro retirado no cambia nada y devuelve el aviso para quien llama
+    pub fn set(&mut self, shader_type: u32, name: &str, values: &[f32]) -> Result<Option<String>, String> {
+        if let Some(old) = retired_params(shader_type).iter().find(|old| old.name == name) {
+            return Ok(Some(format!(
+                "el shader {} ya no usa el parámetro '{}', se ignora (ahora es {})",
+                shader_type, name, old.replacement
+            )));
+        }
+
+        let specs = param_specs(shader_type);
+        let index = specs
+            .iter()
+            .position(|spec| spec.name == name)
+            .ok_or_else(|| {
+                let names: Vec<&str> = specs.iter().map(|spec| spec.name).collect();
+                format!("el shader {} no tiene el parámetro '{}' (tiene: {})", shader_type, name, names.join(", "))
+            })?;
+
+        let value = specs[index].default.with_values(values).ok_or_else(|| {
+            format!("'{}' necesita {} valores, se dieron {}", name, specs[index].default.components(), values.len())
+        })?;
+        self.values[index] = value.to_vec4();
+        Ok(None)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn retired_names_are_ignored_and_unknown_names_fail() {
+        let mut params = ShaderParams::defaults(3);
+        let before = params.values;
+        let warning = params.set(3, "color1", &[1.0, 0.0, 0.0]).unwrap();
+        assert!(warning.is_some_and(|w| w.contains("color1")));
+        assert_eq!(params.values, before);
+        assert_eq!(params.set(3, "band_frequency", &[8.0]), Ok(None));
+        assert!(params.set(3, "color9", &[1.0, 0.0, 0.0]).is_err());
+
+        // Ninguno choca con un parámetro vigente del mismo shader
+        for shader_type in 1..=6 {
+            for old in retired_params(shader_type) {
+                assert!(param_specs(shader_type).iter().all(|spec| spec.name != old.name), "{}", old.name);
+            }
+        }
+    }
+
+    // Las constantes "const <PREFIJO>_<NOMBRE> = <índice>;" de shader.wgsl
+    // tienen que ser el índice de cada parámetro en param_specs
+    #[test]
+    fn wgsl_indices_match_param_specs() {
+        let source = include_str!("shader.wgsl");
+        for (shader_type, prefix) in [(1, "SUN"), (2, "ROCKY"), (3, "GAS"), (4, "RINGED"), (5, "VOLCANIC"), (6, "MOON")] {
+            let mut constants: Vec<(String, usize)> = source
+                .lines()
+                .filter_map(|line| {
+                    let (name, value) = line.strip_prefix("const ")?.strip_suffix(';')?.split_once(" = ")?;
+                    let name = name.strip_prefix(prefix)?.strip_prefix('_')?;
+                    if name.ends_with("_RAMP") {
+                        return None;
+                    }
+                    Some((name.to_lowercase(), value.parse().expect(line)))
+                })
+                .collect();
+            constants.sort_by_key(|&(_, index)| index);
+
+            let expected: Vec<(String, usize)> =
+                param_specs(shader_type).iter().enumerate().map(|(i, spec)| (spec.name.to_string(), i)).collect();
+            assert_eq!(constants, expected, "constantes de {} en shader.wgsl", prefix);
+        }
+    }
+}
@@ -0,0 +1,404 @@
+use nalgebra_glm::Vec3;
+use std::fs::File;
+use std::io::{BufWriter, Error, ErrorKind, Write};
+use crate::obj_loader::Obj;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Encoding {
+    Ascii,
+    BinaryLittleEndian,
+    BinaryBigEndian,
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Scalar {
+    I8,
+    U8,
+    I16,
+    U16,
+    I32,
+    U32,
+    F32,
+    F64,
+}
+
+impl Scalar {
+    fn parse(name: &str) -> Option<Self> {
+        match name {
+            "char" | "int8" => Some(Scalar::I8),
+            "uchar" | "uint8" => Some(Scalar::U8),
+            "short" | "int16" => Some(Scalar::I16),
+            "ushort" | "uint16" => Some(Scalar::U16),
+            "int" | "int32" => Some(Scalar::I32),
+            "uint" | "uint32" => Some(Scalar::U32),
+            "float" | "float32" => Some(Scalar::F32),
+            "double" | "float64" => Some(Scalar::F64),
+            _ => None,
+        }
+    }
+
+    fn size(self) -> usize {
+        match self {
+            Scalar::I8 | Scalar::U8 => 1,
+            Scalar::I16 | Scalar::U16 => 2,
+            Scalar::I32 | Scalar::U32 | Scalar::F32 => 4,
+            Scalar::F64 => 8,
+        }
+    }
+}
+
+enum Property {
+    Value(String, Scalar),
+    List(String, Scalar, Scalar),
+}
+
+struct Element {
+    name: String,
+    count: usize,
+    properties: Vec<Property>,
+}
+
+impl Element {
+    // Lo mínimo que ocupa una fila en el cuerpo: en binario, los valores fijos
+    // y el contador de cada lista; en texto, un carácter por valor
+    fn min_row_bytes(&self, encoding: Encoding) -> usize {
+        self.properties
+            .iter()
+            .map(|property| match (encoding, property) {
+                (Encoding::Ascii, _) => 1,
+                (_, Property::Value(_, scalar)) => scalar.size(),
+                (_, Property::List(_, count_type, _)) => count_type.size(),
+            })
+            .sum()
+    }
+}
+
+fn invalid(filename: &str, message: &str) -> Error {
+    Error::new(ErrorKind::InvalidData, format!("{}: {}", filename, message))
+}
+
+// Lector de valores del cuerpo del archivo, en texto o binario
+struct Body<'a> {
+    encoding: Encoding,
+    data: &'a [u8],
+    offset: usize,
+}
+
+impl Body<'_> {
+    fn read(&mut self, scalar: Scalar) -> Option<f64> {
+        if self.encoding == Encoding::Ascii {
+            let rest = &self.data[self.offset..];
+            let start = rest.iter().position(|b| !b.is_ascii_whitespace())?;
+            let len = rest[start..]
+                .iter()
+                .position(|b| b.is_ascii_whitespace())
+                .unwrap_or(rest.len() - start);
+            self.offset += start + len;
+            return std::str::from_utf8(&rest[start..start + len]).ok()?.parse().ok();
+        }
+
+        let bytes = self.data.get(self.offset..self.offset + scalar.size())?;
+        self.offset += scalar.size();
+
+        let little = self.encoding == Encoding::BinaryLittleEndian;
+        macro_rules! decode {
+            ($t:ty) => {{
+                let array = bytes.try_into().ok()?;
+                (if little { <$t>::from_le_bytes(array) } else { <$t>::from_be_bytes(array) }) as f64
+            }};
+        }
+        Some(match scalar {
+            Scalar::I8 => bytes[0] as i8 as f64,
+            Scalar::U8 => bytes[0] as f64,
+            Scalar::I16 => decode!(i16),
+            Scalar::U16 => decode!(u16),
+            Scalar::I32 => decode!(i32),
+            Scalar::U32 => decode!(u32),
+            Scalar::F32 => decode!(f32),
+            Scalar::F64 => decode!(f64),
+        })
+    }
+}
+
+// PLY en texto, binary_little_endian o binary_big_endian. Se leen posición,
+// normales y coordenadas de textura de "vertex" y los polígonos de "face"
+// (triangulados en abanico); el resto de elementos se ignora
+pub fn load(filename: &str) -> Result<Obj, Error> {
+    let data = std::fs::read(filename)?;
+
+    let header_end = data
+        .windows(10)
+        .position(|w| w == b"end_header")
+        .ok_or_else(|| invalid(filename, "falta end_header"))?;
+    let body_start = data[header_end..]
+        .iter()
+        .position(|&b| b == b'\n')
+        .map(|i| header_end + i + 1)
+        .unwrap_or(data.len());
+    let header = String::from_utf8_lossy(&data[..header_end]);
+
+    let mut lines = header.lines();
+    if lines.next().map(str::trim) != Some("ply") {
+        return Err(invalid(filename, "no es un archivo PLY"));
+    }
+
+    let mut encoding = None;
+    let mut elements: Vec<Element> = Vec::new();
+
+    for line in lines {
+        let parts: Vec<&str> = line.split_whitespace().collect();
+        match parts.as_slice() {
+            ["format", format, ..] => {
+                encoding = match *format {
+                    "ascii" => Some(Encoding::Ascii),
+                    "binary_little_endian" => Some(Encoding::BinaryLittleEndian),
+                    "binary_big_endian" => Some(Encoding::BinaryBigEndian),
+                    _ => return Err(invalid(filename, &format!("formato desconocido '{}'", format))),
+                };
+            }
+            ["element", name, count] => elements.push(Element {
+                name: name.to_string(),
+                count: count.parse().map_err(|_| invalid(filename, line))?,
+                properties: Vec::new(),
+            }),
+            ["property", "list", count_type, item_type, name] => {
+                let count_type = Scalar::parse(count_type).ok_or_else(|| invalid(filename, line))?;
+                let item_type = Scalar::parse(item_type).ok_or_else(|| invalid(filename, line))?;
+                let element = elements.last_mut().ok_or_else(|| invalid(filename, line))?;
+                element.properties.push(Property::List(name.to_string(), count_type, item_type));
+            }
+            ["property", scalar, name] => {
+                let scalar = Scalar::parse(scalar).ok_or_else(|| invalid(filename, line))?;
+                let element = elements.last_mut().ok_or_else(|| invalid(filename, line))?;
+                element.properties.push(Property::Value(name.to_string(), scalar));
+            }
+            _ => {}
+        }
+    }
+
+    let mut body = Body {
+        encoding: encoding.ok_or_else(|| invalid(filename, "falta la línea format"))?,
+        data: &data[body_start..],
+        offset: 0,
+    };
+    let truncated = || invalid(filename, "archivo truncado");
+
+    let mut vertices = Vec::new();
+    let mut normals = Vec::new();
+    let mut tex_coords = Vec::new();
+    let mut indices = Vec::new();
+
+    for element in &elements {
+        // La cantidad viene de la cabecera: sin propiedades no se leería nada y
+        // el bucle no terminaría nunca, y con ellas tiene que caber en lo que
+        // queda del cuerpo
+        if element.count > 0 && element.properties.is_empty() {
+            return Err(invalid(filename, &format!("el elemento '{}' no tiene propiedades", element.name)));
+        }
+        let remaining = body.data.len() - body.offset;
+        if element.count.saturating_mul(element.min_row_bytes(body.encoding)) > remaining {
+            return Err(truncated());
+        }
+
+        for _ in 0..element.count {
+            let mut position = Vec3::zeros();
+            let mut normal = Vec3::zeros();
+            let mut uv = Vec3::zeros();
+            let mut has_normal = false;
+            let mut has_uv = false;
+
+            for property in &element.properties {
+                match property {
+                    Property::Value(name, scalar) => {
+                        let value = body.read(*scalar).ok_or_else(truncated)? as f32;
+                        match name.as_str() {
+                            "x" => position.x = value,
+                            "y" => position.y = value,
+                            "z" => position.z = value,
+                            "nx" => (normal.x, has_normal) = (value, true),
+                            "ny" => (normal.y, has_normal) = (value, true),
+                            "nz" => (normal.z, has_normal) = (value, true),
+                            "u" | "s" | "texture_u" | "texture_s" => (uv.x, has_uv) = (value, true),
+                            "v" | "t" | "texture_v" | "texture_t" => (uv.y, has_uv) = (value, true),
+                            _ => {}
+                        }
+                    }
+                    Property::List(name, count_type, item_type) => {
+                        // Sin reservar: la cantidad viene del archivo y puede ser
+                        // cualquier cosa si está corrupto
+                        let count = body.read(*count_type).ok_or_else(truncated)? as usize;
+                        let mut polygon = Vec::new();
+                        for _ in 0..count {
+                            polygon.push(body.read(*item_type).ok_or_else(truncated)? as u32);
+                        }
+
+                        let is_face_list = name == "vertex_indices" || name == "vertex_index";
+                        if element.name == "face" && is_face_list {
+                            for i in 1..polygon.len().saturating_sub(1) {
+                                indices.extend_from_slice(&[polygon[0], polygon[i], polygon[i + 1]]);
+                            }
+                        }
+                    }
+                }
+            }
+
+            if element.name == "vertex" {
+                vertices.push(position);
+                if has_normal {
+                    normals.push(normal);
+                }
+                if has_uv {
+                    tex_coords.push(uv);
+                }
+            }
+        }
+    }
+
+    if let Some(&bad) = indices.iter().find(|&&i| i as usize >= vertices.len()) {
+        return Err(invalid(filename, &format!("índice de vértice {} fuera de rango", bad)));
+    }
+
+    Ok(Obj::from_indexed(vertices, normals, tex_coords, &indices))
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct PlyOptions {
+    // binary_little_endian, que es lo que espera casi cualquier herramienta
+    pub binary: bool,
+    pub tex_coords: bool,
+    // Como propiedades tx ty tz tw (w es el signo de la bitangente)
+    pub tangents: bool,
+}
+
+// Guarda posición y normal por vértice, y según las opciones coordenadas de
+// textura y tangentes
+pub fn save(obj: &Obj, filename: &str, options: PlyOptions) -> Result<(), Error> {
+    let (vertices, indices) = obj.get_indexed_vertex_array();
+
+    let mut properties = vec!["x", "y", "z", "nx", "ny", "nz"];
+    if options.tex_coords {
+        properties.extend_from_slice(&["s", "t"]);
+    }
+    if options.tangents {
+        properties.extend_from_slice(&["tx", "ty", "tz", "tw"]);
+    }
+
+    let mut file = BufWriter::new(File::create(filename)?);
+    writeln!(file, "ply")?;
+    writeln!(file, "format {} 1.0", if options.binary { "binary_little_endian" } else { "ascii" })?;
+    writeln!(file, "comment lab5-shaders")?;
+    writeln!(file, "element vertex {}", vertices.len())?;
+    for name in &properties {
+        writeln!(file, "property float {}", name)?;
+    }
+    writeln!(file, "element face {}", indices.len() / 3)?;
+    writeln!(file, "property list uchar uint vertex_indices")?;
+    writeln!(file, "end_header")?;
+
+    for v in &vertices {
+        let mut values = vec![v.position.x, v.position.y, v.position.z, v.normal.x, v.normal.y, v.normal.z];
+        if options.tex_coords {
+            values.extend_from_slice(&[v.tex_coords.x, v.tex_coords.y]);
+        }
+        if options.tangents {
+            values.extend_from_slice(&[v.tangent.x, v.tangent.y, v.tangent.z, v.tangent.w]);
+        }
+
+        if options.binary {
+            for value in values {
+                file.write_all(&value.to_le_bytes())?;
+            }
+        } else {
+            let line: Vec<String> = values.iter().map(|value| format!("{:.6}", value)).collect();
+            writeln!(file, "{}", line.join(" "))?;
+        }
+    }
+
+    for triangle in indices.chunks_exact(3) {
+        if options.binary {
+            file.write_all(&[3u8])?;
+            for index in triangle {
+                file.write_all(&index.to_le_bytes())?;
+            }
+        } else {
+            writeln!(file, "3 {} {} {}", triangle[0], triangle[1], triangle[2])?;
+        }
+    }
+
+    file.flush()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_path(name: &str) -> String {
+        std::env::temp_dir().join(format!("lab5-ply-{}-{}", std::process::id(), name)).to_string_lossy().into_owned()
+    }
+
+    fn quad() -> Obj {
+        let vertices = vec![
+            Vec3::new(0.0, 0.0, 0.0),
+            Vec3::new(1.0, 0.0, 0.0),
+            Vec3::new(1.0, 1.0, 0.0),
+            Vec3::new(0.0, 1.0, 0.0),
+        ];
+        let normals = vec![Vec3::z(); 4];
+        Obj::from_indexed(vertices, normals, Vec::new(), &[0, 1, 2, 0, 2, 3])
+    }
+
+    #[test]
+    fn round_trip_ascii_and_binary() {
+        let obj = quad();
+        let (expected, expected_indices) = obj.get_indexed_vertex_array();
+        for binary in [false, true] {
+            let path = temp_path(if binary { "rt.bin.ply" } else { "rt.txt.ply" });
+            save(&obj, &path, PlyOptions { binary, ..PlyOptions::default() }).unwrap();
+            let (vertices, indices) = load(&path).unwrap().get_indexed_vertex_array();
+            std::fs::remove_file(&path).unwrap();
+
+            assert_eq!(indices, expected_indices);
+            for (a, b) in vertices.iter().zip(&expected) {
+                assert!((a.position - b.position).norm() < 1e-5);
+                assert!((a.normal - b.normal).norm() < 1e-5);
+            }
+        }
+    }
+
+    #[test]
+    fn truncated_input_is_an_error() {
+        let path = temp_path("truncated.ply");
+        save(&quad(), &path, PlyOptions { binary: true, ..PlyOptions::default() }).unwrap();
+        let data = std::fs::read(&path).unwrap();
+
+        std::fs::write(&path, &data[..data.len() - 5]).unwrap();
+        assert!(load(&path).is_err());
+
+        // Una lista que dice tener 4 mil millones de índices
+        let header = "ply\nformat binary_little_endian 1.0\nelement face 1\nproperty list uint uint vertex_indices\nend_header\n";
+        let mut huge = header.as_bytes().to_vec();
+        huge.extend_from_slice(&u32::MAX.to_le_bytes());
+        huge.extend_from_slice(&[0; 8]);
+        std::fs::write(&path, &huge).unwrap();
+        assert!(load(&path).is_err());
+
+        // Un billón de vértices sin propiedades no lee nada del cuerpo
+        let empty = "ply\nformat ascii 1.0\nelement vertex 1000000000000\nend_header\n";
+        std::fs::write(&path, empty).unwrap();
+        assert!(load(&path).is_err());
+
+        // Más filas de las que caben en el cuerpo, en texto y en binario
+        for format in ["ascii", "binary_little_endian"] {
+            let header = format!(
+                "ply\nformat {} 1.0\nelement vertex 1000000000000\nproperty float x\nend_header\n",
+                format
+            );
+            let mut rows = header.into_bytes();
+            rows.extend_from_slice(b"0 1 2 3 ");
+            std::fs::write(&path, &rows).unwrap();
+            assert!(load(&path).is_err());
+        }
+
+        std::fs::remove_file(&path).unwrap();
+    }
+}
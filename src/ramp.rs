@@ -0,0 +1,272 @@
+// Rampas de color: paradas (posición, color) y cómo se pasa de una a otra.
+// sample_ramp en shader.wgsl hace lo mismo con el bloque ShaderRamps; el orden
+// de cada lista de rampas es el índice en shader.wgsl (constantes *_RAMP)
+
+pub const MAX_RAMPS: usize = 4;
+pub const MAX_STOPS: usize = 8;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Interpolation {
+    Linear,
+    Smoothstep,
+    // Cada parada mantiene su color hasta la siguiente
+    Constant,
+}
+
+impl Interpolation {
+    pub fn parse(name: &str) -> Option<Self> {
+        match name {
+            "linear" => Some(Interpolation::Linear),
+            "smoothstep" => Some(Interpolation::Smoothstep),
+            "constant" => Some(Interpolation::Constant),
+            _ => None,
+        }
+    }
+
+    // Código que usa el WGSL
+    fn code(self) -> u32 {
+        match self {
+            Interpolation::Linear => 0,
+            Interpolation::Smoothstep => 1,
+            Interpolation::Constant => 2,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ColorRamp {
+    pub interpolation: Interpolation,
+    // Ordenadas por posición
+    pub stops: Vec<(f32, [f32; 3])>,
+}
+
+impl ColorRamp {
+    pub fn new(interpolation: Interpolation, stops: &[(f32, [f32; 3])]) -> Self {
+        let mut stops = stops.to_vec();
+        stops.sort_by(|a, b| a.0.total_cmp(&b.0));
+        ColorRamp { interpolation, stops }
+    }
+
+    // "<interpolación> <pos> <r> <g> <b> [<pos> <r> <g> <b> ...]"
+    pub fn parse(words: &[&str]) -> Result<Self, String> {
+        let (mode, values) = words.split_first().ok_or("falta la interpolación")?;
+        let interpolation = Interpolation::parse(mode)
+            .ok_or_else(|| format!("interpolación desconocida '{}' (linear, smoothstep o constant)", mode))?;
+
+        let values = values
+            .iter()
+            .map(|v| v.parse::<f32>().map_err(|_| format!("'{}' no es un número", v)))
+            .collect::<Result<Vec<_>, _>>()?;
+        if values.is_empty() || values.len() % 4 != 0 {
+            return Err("cada parada necesita posición y color: <pos> <r> <g> <b>".to_string());
+        }
+        if values.len() / 4 > MAX_STOPS {
+            return Err(format!("{} paradas, el máximo es {}", values.len() / 4, MAX_STOPS));
+        }
+
+        let stops: Vec<(f32, [f32; 3])> = values.chunks_exact(4).map(|c| (c[0], [c[1], c[2], c[3]])).collect();
+        Ok(ColorRamp::new(interpolation, &stops))
+    }
+
+    pub fn sample(&self, t: f32) -> [f32; 3] {
+        let Some(&(first_pos, first_color)) = self.stops.first() else {
+            return [0.0; 3];
+        };
+        if t <= first_pos {
+            return first_color;
+        }
+
+        for pair in self.stops.windows(2) {
+            let (pos_a, color_a) = pair[0];
+            let (pos_b, color_b) = pair[1];
+            if t < pos_b {
+                let f = (t - pos_a) / (pos_b - pos_a).max(1e-6);
+                let f = match self.interpolation {
+                    Interpolation::Linear => f,
+                    Interpolation::Smoothstep => f * f * (3.0 - 2.0 * f),
+                    Interpolation::Constant => 0.0,
+                };
+                return [
+                    color_a[0] + (color_b[0] - color_a[0]) * f,
+                    color_a[1] + (color_b[1] - color_a[1]) * f,
+                    color_a[2] + (color_b[2] - color_a[2]) * f,
+                ];
+            }
+        }
+
+        self.stops[self.stops.len() - 1].1
+    }
+
+    fn to_gpu(&self) -> GpuRamp {
+        let mut stops = [[0.0; 4]; MAX_STOPS];
+        for (slot, &(pos, [r, g, b])) in stops.iter_mut().zip(&self.stops) {
+            *slot = [r, g, b, pos];
+        }
+        GpuRamp {
+            stops,
+            count: self.stops.len().min(MAX_STOPS) as u32,
+            interpolation: self.interpolation.code(),
+            _padding: [0; 2],
+        }
+    }
+}
+
+pub struct RampSpec {
+    pub name: &'static str,
+    pub interpolation: Interpolation,
+    pub stops: &'static [(f32, [f32; 3])],
+}
+
+const fn ramp(name: &'static str, interpolation: Interpolation, stops: &'static [(f32, [f32; 3])]) -> RampSpec {
+    RampSpec { name, interpolation, stops }
+}
+
+const SUN_RAMPS: &[RampSpec] = &[
+    // Por temperatura del plasma
+    ramp("temperature", Interpolation::Smoothstep, &[
+        (0.25, [1.0, 0.55, 0.20]),
+        (0.45, [1.0, 0.78, 0.39]),
+        (0.65, [1.0, 0.96, 0.78]),
+        (0.85, [1.0, 1.0, 0.98]),
+    ]),
+];
+
+const ROCKY_RAMPS: &[RampSpec] = &[
+    // Por profundidad bajo el nivel del mar
+    ramp("ocean", Interpolation::Linear, &[
+        (0.3, [0.12, 0.31, 0.63]),
+        (0.9, [0.04, 0.16, 0.39]),
+    ]),
+    // Por densidad de vegetación: desierto, pastizal, bosque
+    ramp("vegetation", Interpolation::Smoothstep, &[
+        (0.3, [0.82, 0.75, 0.55]),
+        (0.5, [0.39, 0.63, 0.27]),
+        (0.7, [0.20, 0.55, 0.20]),
+    ]),
+];
+
+const GAS_RAMPS: &[RampSpec] = &[
+    // Cíclica para que las bandas no salten de color
+    ramp("bands", Interpolation::Linear, &[
+        (0.0, [0.98, 0.90, 0.75]),
+        (0.25, [0.67, 0.47, 0.31]),
+        (0.5, [0.82, 0.71, 0.55]),
+        (0.75, [1.0, 0.96, 0.86]),
+        (1.0, [0.98, 0.90, 0.75]),
+    ]),
+];
+
+const RINGED_RAMPS: &[RampSpec] = &[
+    ramp("rings", Interpolation::Smoothstep, &[
+        (0.25, [0.71, 0.63, 0.49]),
+        (0.55, [0.82, 0.73, 0.57]),
+        (0.85, [0.96, 0.88, 0.75]),
+    ]),
+];
+
+const VOLCANIC_RAMPS: &[RampSpec] = &[
+    // Por calor: roja, naranja, amarilla, blanca
+    ramp("lava", Interpolation::Linear, &[
+        (0.25, [0.86, 0.27, 0.12]),
+        (0.45, [1.0, 0.59, 0.20]),
+        (0.65, [1.0, 0.90, 0.47]),
+        (0.85, [1.0, 1.0, 0.86]),
+    ]),
+    ramp("rock", Interpolation::Smoothstep, &[
+        (0.5, [0.14, 0.12, 0.10]),
+        (0.7, [0.27, 0.24, 0.22]),
+    ]),
+];
+
+const MOON_RAMPS: &[RampSpec] = &[
+    // Por altura de las tierras altas, fuera de cráteres y mares
+    ramp("highlands", Interpolation::Smoothstep, &[
+        (0.6, [0.55, 0.55, 0.55]),
+        (0.8, [0.75, 0.75, 0.75]),
+    ]),
+];
+
+// Rampas que declara cada tipo de shader (vacío para el PBR)
+pub fn ramp_specs(shader_type: u32) -> &'static [RampSpec] {
+    match shader_type {
+        1 => SUN_RAMPS,
+        2 => ROCKY_RAMPS,
+        3 => GAS_RAMPS,
+        4 => RINGED_RAMPS,
+        5 => VOLCANIC_RAMPS,
+        6 => MOON_RAMPS,
+        _ => &[],
+    }
+}
+
+pub fn default_ramps(shader_type: u32) -> Vec<ColorRamp> {
+    ramp_specs(shader_type)
+        .iter()
+        .map(|spec| ColorRamp::new(spec.interpolation, spec.stops))
+        .collect()
+}
+
+// Reemplaza una rampa por nombre; el error dice cuáles tiene el shader
+pub fn set_ramp(ramps: &mut [ColorRamp], shader_type: u32, name: &str, ramp: &ColorRamp) -> Result<(), String> {
+    let specs = ramp_specs(shader_type);
+    let index = specs.iter().position(|spec| spec.name == name).ok_or_else(|| {
+        let names: Vec<&str> = specs.iter().map(|spec| spec.name).collect();
+        format!("el shader {} no tiene la rampa '{}' (tiene: {})", shader_type, name, names.join(", "))
+    })?;
+    ramps[index] = ramp.clone();
+    Ok(())
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct GpuRamp {
+    // rgb color, w posición
+    stops: [[f32; 4]; MAX_STOPS],
+    count: u32,
+    interpolation: u32,
+    _padding: [u32; 2],
+}
+
+// Bloque que se sube como uniform (binding 2)
+#[repr(C)]
+#[derive(Debug, Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct ShaderRamps {
+    pub ramps: [GpuRamp; MAX_RAMPS],
+}
+
+impl ShaderRamps {
+    pub fn new(ramps: &[ColorRamp]) -> Self {
+        let mut block = ShaderRamps { ramps: [bytemuck::Zeroable::zeroed(); MAX_RAMPS] };
+        for (slot, ramp) in block.ramps.iter_mut().zip(ramps) {
+            *slot = ramp.to_gpu();
+        }
+        block
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Las constantes *_RAMP de shader.wgsl tienen que ser el índice de cada
+    // rampa en ramp_specs
+    #[test]
+    fn wgsl_indices_match_ramp_specs() {
+        let source = include_str!("shader.wgsl");
+        for (shader_type, prefix) in [(1, "SUN"), (2, "ROCKY"), (3, "GAS"), (4, "RINGED"), (5, "VOLCANIC"), (6, "MOON")] {
+            let mut constants: Vec<(String, usize)> = source
+                .lines()
+                .filter_map(|line| {
+                    let (name, value) = line.strip_prefix("const ")?.strip_suffix(';')?.split_once(" = ")?;
+                    let name = name.strip_prefix(prefix)?.strip_prefix('_')?.strip_suffix("_RAMP")?;
+                    Some((name.to_lowercase(), value.parse().expect(line)))
+                })
+                .collect();
+            constants.sort_by_key(|&(_, index)| index);
+
+            let expected: Vec<(String, usize)> =
+                ramp_specs(shader_type).iter().enumerate().map(|(i, spec)| (spec.name.to_string(), i)).collect();
+            assert_eq!(constants, expected, "rampas de {} en shader.wgsl", prefix);
+        }
+    }
+}
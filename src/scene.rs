@@ -0,0 +1,397 @@
+use std::fs::File;
+use nalgebra_glm::{self as glm, Mat4, Vec3};
+use std::io::{BufRead, BufReader, Error, ErrorKind};
+use crate::geometry::SphereMesh;
+use crate::obj_loader::{NormalMode, DEFAULT_CREASE_ANGLE};
+use crate::params::ShaderParams;
+use crate::ramp::{default_ramps, set_ramp, ColorRamp};
+use crate::sky::Sky;
+
+pub const DEFAULT_LOD_LEVELS: u32 = 5;
+// Irradiancia del sol a distancia 1; los planetas de la escena por defecto
+// están a unos 0.6, donde llega casi 1
+pub const DEFAULT_LIGHT_INTENSITY: f32 = 0.3;
+// Segundos por vuelta sobre su eje
+pub const DEFAULT_ROTATION_PERIOD: f32 = 20.0;
+
+// Luz puntual que ilumina la escena, en coordenadas del mundo (x, y como en
+// pantalla, z hacia el observador)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Light {
+    pub position: [f32; 3],
+    pub intensity: f32,
+    // Radio del disco del sol, para la penumbra de los eclipses (0 = puntual)
+    pub radius: f32,
+}
+
+// Capa de nubes aparte de la superficie, un poco por encima de ella
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Clouds {
+    // Altura sobre la superficie, como fracción del radio
+    pub altitude: f32,
+    // Umbral del ruido a partir del cual hay nubes (más alto, menos nubes)
+    pub cover: f32,
+    // Velocidad de giro respecto a la del cuerpo
+    pub speed: f32,
+}
+
+impl Default for Clouds {
+    fn default() -> Self {
+        Clouds { altitude: 0.03, cover: 0.6, speed: 1.3 }
+    }
+}
+
+// Giro del cuerpo sobre su eje (el y del cuerpo), con el eje inclinado hacia
+// la derecha de la pantalla
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Spin {
+    // Inclinación del eje en grados
+    pub tilt: f32,
+    // Segundos por vuelta; 0 no gira, negativo gira al revés (retrógrado)
+    pub period: f32,
+}
+
+impl Default for Spin {
+    fn default() -> Self {
+        Spin { tilt: 0.0, period: DEFAULT_ROTATION_PERIOD }
+    }
+}
+
+impl Spin {
+    // Ángulo girado hasta ese momento, en radianes
+    pub fn angle(&self, time: f32) -> f32 {
+        if self.period == 0.0 {
+            return 0.0;
+        }
+        std::f32::consts::TAU * (time / self.period).fract()
+    }
+
+    // Del marco del cuerpo al mundo, sin escala ni traslación
+    pub fn rotation(&self, time: f32) -> Mat4 {
+        let tilt = glm::rotation(-self.tilt.to_radians(), &Vec3::z());
+        tilt * glm::rotation(self.angle(time), &Vec3::y())
+    }
+
+    // Del marco del cuerpo (esfera unitaria) al mundo, para un cuerpo en esa
+    // posición y con ese radio
+    pub fn model_matrix(&self, position: [f32; 2], scale: f32, time: f32) -> Mat4 {
+        let translation = glm::translation(&Vec3::new(position[0], position[1], 0.0));
+        translation * self.rotation(time) * glm::scaling(&Vec3::new(scale, scale, scale))
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Body {
+    pub name: String,
+    pub shader_type: u32,
+    pub position: [f32; 2],
+    pub scale: f32,
+    pub mesh: Option<String>,
+    // Esfera a usar cuando no hay mesh
+    pub sphere: SphereMesh,
+    // Niveles de detalle de la esfera (1 desactiva el LOD)
+    pub lod_levels: u32,
+    // Amplitud del relieve del terreno (fracción del radio); 0 lo desactiva
+    pub displacement: f32,
+    // Semilla del ruido: cuerpos del mismo tipo con distinta semilla son variantes distintas
+    pub seed: u32,
+    // Parámetros del shader que cambian su valor por defecto, por nombre
+    pub params: Vec<(String, Vec<f32>)>,
+    // Rampas de color que reemplazan las del shader, por nombre
+    pub ramps: Vec<(String, ColorRamp)>,
+    // Normales a generar para la malla; None usa las del archivo (si las trae)
+    pub normals: Option<NormalMode>,
+    // Intensidad de la luz que emite si es un sol (shader 1)
+    pub light: f32,
+    // Espesor de la capa de atmósfera (fracción del radio); 0 sin atmósfera
+    pub atmosphere: f32,
+    pub clouds: Option<Clouds>,
+    pub spin: Spin,
+}
+
+impl Body {
+    pub fn new(name: &str, shader_type: u32, position: [f32; 2], scale: f32) -> Self {
+        Body {
+            name: name.to_string(),
+            shader_type,
+            position,
+            scale,
+            mesh: None,
+            sphere: SphereMesh::default(),
+            lod_levels: DEFAULT_LOD_LEVELS,
+            displacement: 0.0,
+            seed: 0,
+            params: Vec::new(),
+            ramps: Vec::new(),
+            normals: None,
+            light: DEFAULT_LIGHT_INTENSITY,
+            atmosphere: 0.0,
+            clouds: None,
+            spin: Spin::default(),
+        }
+    }
+
+    // Del marco del cuerpo al mundo en ese momento
+    pub fn model_matrix(&self, time: f32) -> Mat4 {
+        self.spin.model_matrix(self.position, self.scale, time)
+    }
+
+    // Parámetros por defecto de su shader con los de la escena encima, y los
+    // avisos de los parámetros retirados que se ignoraron
+    pub fn shader_params(&self) -> Result<(ShaderParams, Vec<String>), String> {
+        let mut params = ShaderParams::defaults(self.shader_type);
+        let mut warnings = Vec::new();
+        for (name, values) in &self.params {
+            warnings.extend(params.set(self.shader_type, name, values)?);
+        }
+        Ok((params, warnings))
+    }
+
+    // Rampas por defecto de su shader con las de la escena encima
+    pub fn color_ramps(&self) -> Result<Vec<ColorRamp>, String> {
+        let mut ramps = default_ramps(self.shader_type);
+        for (name, ramp) in &self.ramps {
+            set_ramp(&mut ramps, self.shader_type, name, ramp)?;
+        }
+        Ok(ramps)
+    }
+}
+
+pub struct Scene {
+    pub bodies: Vec<Body>,
+    // Estrellas y Vía Láctea del fondo
+    pub sky: Sky,
+}
+
+impl Scene {
+    // Formato por líneas, como los OBJ: cada "body" abre un cuerpo nuevo y las
+    // líneas siguientes ajustan sus propiedades
+    //
+    //   body rocoso
+    //   shader 2
+    //   position -0.6 0.2
+    //   scale 0.15
+    //   sphere icosphere 5
+    //   lod 3
+    //   displacement 0.05
+    //   seed 42
+    //   param ocean_level 0.4
+    //   ramp vegetation smoothstep 0.3 0.8 0.7 0.5 0.7 0.2 0.5 0.2
+    //   mesh assets/sphere.obj
+    //   normals smooth 45
+    //   light 0.3
+    //   atmosphere 0.08
+    //   clouds 0.03 0.6 1.3
+    //   tilt 23.4
+    //   rotation 20
+    //
+    // "sky <semilla> [estrellas]" vale para toda la escena, en cualquier lugar
+    pub fn load(filename: &str) -> Result<Self, Error> {
+        let file = File::open(filename)?;
+        let reader = BufReader::new(file);
+
+        let mut bodies: Vec<Body> = Vec::new();
+        let mut sky = Sky::default();
+
+        for (line_number, line) in reader.lines().enumerate() {
+            let line = line?;
+            let parts: Vec<&str> = line.split_whitespace().collect();
+
+            if parts.is_empty() || parts[0].starts_with('#') {
+                continue;
+            }
+
+            if parts[0] == "sky" && parts.len() >= 2 {
+                sky.seed = parts[1].parse().unwrap_or(sky.seed);
+                if let Some(stars) = parts.get(2) {
+                    sky.stars = stars.parse().unwrap_or(sky.stars);
+                }
+                continue;
+            }
+
+            if parts[0] == "body" {
+                let name = parts.get(1).copied().unwrap_or("cuerpo");
+                bodies.push(Body::new(name, 1, [0.0, 0.0], 0.2));
+                continue;
+            }
+
+            let body = bodies.last_mut().ok_or_else(|| {
+                Error::new(
+                    ErrorKind::InvalidData,
+                    format!("{}:{}: '{}' fuera de un bloque body", filename, line_number + 1, parts[0]),
+                )
+            })?;
+
+            match parts[0] {
+                "shader" if parts.len() >= 2 => {
+                    body.shader_type = parts[1].parse().unwrap_or(body.shader_type);
+                }
+                "position" if parts.len() >= 3 => {
+                    let x: f32 = parts[1].parse().unwrap_or(0.0);
+                    let y: f32 = parts[2].parse().unwrap_or(0.0);
+                    body.position = [x, y];
+                }
+                "scale" if parts.len() >= 2 => {
+                    body.scale = parts[1].parse().unwrap_or(body.scale);
+                }
+                "mesh" if parts.len() >= 2 => {
+                    body.mesh = Some(parts[1..].join(" "));
+                }
+                "sphere" if parts.len() >= 2 => {
+                    let resolution = parts.get(2).and_then(|r| r.parse().ok());
+                    body.sphere = SphereMesh::parse(parts[1], resolution).ok_or_else(|| {
+                        Error::new(
+                            ErrorKind::InvalidData,
+                            format!("{}:{}: tipo de esfera desconocido '{}'", filename, line_number + 1, parts[1]),
+                        )
+                    })?;
+                }
+                "displacement" if parts.len() >= 2 => {
+                    body.displacement = parts[1].parse().unwrap_or(body.displacement);
+                }
+                "seed" if parts.len() >= 2 => {
+                    body.seed = parts[1].parse().unwrap_or(body.seed);
+                }
+                "param" if parts.len() >= 3 => {
+                    let values = parts[2..]
+                        .iter()
+                        .map(|v| v.parse::<f32>())
+                        .collect::<Result<Vec<_>, _>>()
+                        .map_err(|_| {
+                            Error::new(
+                                ErrorKind::InvalidData,
+                                format!("{}:{}: valores no numéricos en '{}'", filename, line_number + 1, line.trim()),
+                            )
+                        })?;
+                    match (parts[1], values.as_slice()) {
+                        // Las nubes eran parámetros del shader rocoso antes de
+                        // tener su propia capa: ahora encienden la capa
+                        ("cloud_cover", &[cover]) => body.clouds.get_or_insert_with(Clouds::default).cover = cover,
+                        ("cloud_speed", &[speed]) => body.clouds.get_or_insert_with(Clouds::default).speed = speed,
+                        _ => body.params.push((parts[1].to_string(), values)),
+                    }
+                }
+                "ramp" if parts.len() >= 3 => {
+                    let ramp = ColorRamp::parse(&parts[2..]).map_err(|e| {
+                        Error::new(ErrorKind::InvalidData, format!("{}:{}: rampa '{}': {}", filename, line_number + 1, parts[1], e))
+                    })?;
+                    body.ramps.push((parts[1].to_string(), ramp));
+                }
+                "atmosphere" if parts.len() >= 2 => {
+                    body.atmosphere = parts[1].parse().unwrap_or(body.atmosphere).max(0.0);
+                }
+                "clouds" if parts.len() >= 2 => {
+                    let defaults = Clouds::default();
+                    let value = |i: usize, default: f32| parts.get(i).and_then(|v| v.parse().ok()).unwrap_or(default);
+                    body.clouds = Some(Clouds {
+                        altitude: value(1, defaults.altitude),
+                        cover: value(2, defaults.cover),
+                        speed: value(3, defaults.speed),
+                    });
+                }
+                "tilt" if parts.len() >= 2 => {
+                    body.spin.tilt = parts[1].parse().unwrap_or(body.spin.tilt);
+                }
+                "rotation" if parts.len() >= 2 => {
+                    body.spin.period = parts[1].parse().unwrap_or(body.spin.period);
+                }
+                "light" if parts.len() >= 2 => {
+                    body.light = parts[1].parse().unwrap_or(body.light);
+                }
+                "lod" if parts.len() >= 2 => {
+                    body.lod_levels = parts[1].parse().unwrap_or(body.lod_levels).max(1);
+                }
+                "normals" if parts.len() >= 2 => {
+                    body.normals = match parts[1] {
+                        "flat" => Some(NormalMode::Flat),
+                        "smooth" => {
+                            let angle = parts.get(2).and_then(|a| a.parse().ok());
+                            Some(NormalMode::Smooth(angle.unwrap_or(DEFAULT_CREASE_ANGLE)))
+                        }
+                        _ => None,
+                    };
+                }
+                _ => {}
+            }
+        }
+
+        // Los nombres se comprueban al final porque "shader" puede venir después de "param"
+        for body in &bodies {
+            body.shader_params()
+                .and(body.color_ramps())
+                .map_err(|e| Error::new(ErrorKind::InvalidData, format!("{}: cuerpo '{}': {}", filename, body.name, e)))?;
+        }
+
+        Ok(Scene { bodies, sky })
+    }
+
+    // La luz sale del primer sol; sin sol, desde el observador
+    pub fn light(&self) -> Light {
+        match self.bodies.iter().find(|body| body.shader_type == 1) {
+            Some(sun) => Light {
+                position: [sun.position[0], sun.position[1], 0.0],
+                intensity: sun.light,
+                radius: sun.scale,
+            },
+            None => Light { position: [0.0, 0.0, 2.0], intensity: 4.0 * DEFAULT_LIGHT_INTENSITY, radius: 0.0 },
+        }
+    }
+
+    // Esferas que pueden tapar al sol, una por cuerpo en el orden de la escena:
+    // centro en el mundo y radio (0 para los soles, que no hacen sombra)
+    pub fn occluders(&self) -> Vec<[f32; 4]> {
+        self.bodies
+            .iter()
+            .map(|body| {
+                let radius = if body.shader_type == 1 { 0.0 } else { body.scale };
+                [body.position[0], body.position[1], 0.0, radius]
+            })
+            .collect()
+    }
+
+    pub fn body_mut(&mut self, name: &str) -> Option<&mut Body> {
+        self.bodies.iter_mut().find(|body| body.name == name)
+    }
+}
+
+impl Default for Scene {
+    fn default() -> Self {
+        Scene {
+            bodies: vec![
+                Body::new("sol", 1, [0.0, 0.0], 0.4),           // Sol (centro, grande, amarillo)
+                // Planeta rocoso (izq arriba, pequeño), con nubes y atmósfera
+                Body {
+                    atmosphere: 0.08,
+                    clouds: Some(Clouds::default()),
+                    spin: Spin { tilt: 23.4, ..Spin::default() },
+                    ..Body::new("rocoso", 2, [-0.6, 0.2], 0.15)
+                },
+                Body::new("volcanico", 5, [-0.5, -0.3], 0.18),  // Planeta volcánico (izq abajo, rojo lava)
+                // Gigante gaseoso (der arriba, bandas), gira rápido
+                Body { spin: Spin { tilt: 3.1, period: 10.0 }, ..Body::new("gaseoso", 3, [0.5, 0.3], 0.3) },
+                // Planeta con anillos (der abajo), anillos inclinados con el eje
+                Body { spin: Spin { tilt: 26.7, period: 12.0 }, ..Body::new("anillos", 4, [0.6, -0.2], 0.25) },
+                Body::new("luna", 6, [0.2, -0.5], 0.12),        // Luna/planeta helado (abajo centro)
+            ],
+            sky: Sky::default(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn old_cloud_params_map_to_the_cloud_layer() {
+        let path = std::env::temp_dir().join(format!("lab5-scene-{}.txt", std::process::id()));
+        std::fs::write(&path, "body rocoso\nshader 2\nparam cloud_cover 0.7\nparam cloud_speed 2\nparam ocean_level 0.4\n").unwrap();
+        let scene = Scene::load(path.to_str().unwrap()).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        let body = &scene.bodies[0];
+        assert_eq!(body.clouds, Some(Clouds { cover: 0.7, speed: 2.0, ..Clouds::default() }));
+        assert_eq!(body.params, vec![("ocean_level".to_string(), vec![0.4])]);
+        assert_eq!(body.shader_params().map(|(_, warnings)| warnings), Ok(Vec::new()));
+    }
+}
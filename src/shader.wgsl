@@ -6,7 +6,42 @@ struct Uniforms {
     resolution: vec2<f32>,
     planet_position: vec2<f32>,
     planet_scale: f32,
-    _padding: f32,
+    // Amplitud del relieve (fracción del radio); 0 deja la esfera lisa
+    displacement: f32,
+    node_transform: mat4x4<f32>,
+    // Inversa transpuesta de node_transform, para las normales
+    node_normal_matrix: mat4x4<f32>,
+    base_color: vec4<f32>,
+    emissive: vec3<f32>,
+    metallic: f32,
+    roughness: f32,
+    // Semilla de todo el ruido del cuerpo
+    seed: u32,
+    // Luz puntual del sol: posición en el mundo e intensidad (irradiancia a distancia 1)
+    light_position: vec3<f32>,
+    light_intensity: f32,
+    // Dirección hacia el observador, para la luz especular, el limbo y los
+    // rayos de la atmósfera. La proyección es ortográfica, así que es la misma
+    // en todo punto
+    view_dir: vec3<f32>,
+    // Espesor de la atmósfera como fracción del radio; 0 sin atmósfera
+    atmosphere: f32,
+    // Capa de nubes: altura (fracción del radio, 0 sin nubes), cobertura y
+    // velocidad de giro respecto al cuerpo
+    cloud_altitude: f32,
+    cloud_cover: f32,
+    cloud_speed: f32,
+    body_index: u32,
+    // Del marco del cuerpo al mundo: posición, radio, inclinación y giro
+    model_matrix: mat4x4<f32>,
+    // Inversa transpuesta de model_matrix, calculada en la CPU una vez por
+    // dibujo: lleva las normales al mundo
+    normal_matrix: mat4x4<f32>,
+    // Del mundo al espacio de recorte: vista y proyección ortográfica de la
+    // cámara (Camera::view_projection), la misma que orienta el fondo
+    view_projection: mat4x4<f32>,
+    // Ángulo girado por el cuerpo sobre su eje
+    spin_angle: f32,
 }
 
 @group(0) @binding(0)
@@ -15,51 +50,238 @@ var<uniform> uniforms: Uniforms;
 struct VertexInput {
     @location(0) position: vec3<f32>,
     @location(1) normal: vec3<f32>,
+    @location(2) tangent: vec4<f32>,
 }
 
 struct VertexOutput {
     @builtin(position) clip_position: vec4<f32>,
     @location(0) world_pos: vec3<f32>,
     @location(1) normal: vec3<f32>,
+    // xyz tangente, w signo de la bitangente: b = w * cross(n, t)
+    @location(2) tangent: vec4<f32>,
+    // Posición en el marco del cuerpo, escalada pero sin girar ni trasladar
+    // (dominio del ruido: el patrón gira con la superficie)
+    @location(3) local_pos: vec3<f32>,
+}
+
+// Todo lo que se dibuja en el mundo pasa por la cámara
+fn project(world_pos: vec3<f32>) -> vec4<f32> {
+    return uniforms.view_projection * vec4<f32>(world_pos, 1.0);
+}
+
+// Ejes derecho y arriba de la pantalla en el mundo: las filas de la vista,
+// que en una proyección ortográfica sólo están escaladas
+fn screen_axes() -> mat2x3<f32> {
+    let m = uniforms.view_projection;
+    return mat2x3<f32>(
+        normalize(vec3<f32>(m[0].x, m[1].x, m[2].x)),
+        normalize(vec3<f32>(m[0].y, m[1].y, m[2].y)),
+    );
+}
+
+// Centro del cuerpo en el mundo: la traslación de su matriz de modelo
+fn body_center() -> vec3<f32> {
+    return uniforms.model_matrix[3].xyz;
 }
 
 @vertex
 fn vs_main(input: VertexInput) -> VertexOutput {
     var output: VertexOutput;
+    set_noise_seed(uniforms.seed);
     
-    // Aplicar rotación
-    let angle = uniforms.time * 0.3;
-    let cos_a = cos(angle);
-    let sin_a = sin(angle);
+    // Transformación del nodo (modelos glTF). Las tangentes siguen a la
+    // superficie como las posiciones; las normales, con la inversa transpuesta
+    var surface_pos = (uniforms.node_transform * vec4<f32>(input.position, 1.0)).xyz;
+    var surface_normal = (uniforms.node_normal_matrix * vec4<f32>(input.normal, 0.0)).xyz;
+    var surface_tangent = (uniforms.node_transform * vec4<f32>(input.tangent.xyz, 0.0)).xyz;
     
-    let rot_y = mat3x3<f32>(
-        vec3<f32>(cos_a, 0.0, sin_a),
-        vec3<f32>(0.0, 1.0, 0.0),
-        vec3<f32>(-sin_a, 0.0, cos_a)
-    );
+    // Relieve: se desplaza el vértice y dos vecinos sobre la tangente y la
+    // bitangente, y la normal sale del plano que forman
+    if (uniforms.displacement != 0.0) {
+        let n = normalize(surface_normal);
+        let t = normalize(surface_tangent - n * dot(surface_tangent, n));
+        let b = cross(n, t);
+        let eps = 0.01;
+        
+        let p0 = displace(surface_pos, n);
+        let pt = displace(surface_pos + t * eps, n);
+        let pb = displace(surface_pos + b * eps, n);
+        
+        surface_pos = p0;
+        surface_normal = cross(pt - p0, pb - p0);
+        surface_tangent = pt - p0;
+    }
     
-    // Escalar y rotar
-    let scaled_pos = input.position * uniforms.planet_scale;
-    let rotated_pos = rot_y * scaled_pos;
-    let rotated_normal = rot_y * input.normal;
+    // Al mundo: el cuerpo inclinado, girado, escalado y en su posición
+    let world_pos = (uniforms.model_matrix * vec4<f32>(surface_pos, 1.0)).xyz;
     
-    // Proyección simple con offset de posición
-    let pos = rotated_pos * vec3<f32>(1.0, 1.0, 0.5);
-    output.clip_position = vec4<f32>(pos.xy + uniforms.planet_position, 0.5, 1.0);
-    output.world_pos = rotated_pos;
-    output.normal = normalize(rotated_normal);
+    output.clip_position = project(world_pos);
+    output.world_pos = world_pos;
+    output.local_pos = surface_pos * uniforms.planet_scale;
+    output.normal = normalize((uniforms.normal_matrix * vec4<f32>(surface_normal, 0.0)).xyz);
+    output.tangent = vec4<f32>(normalize((uniforms.model_matrix * vec4<f32>(surface_tangent, 0.0)).xyz), input.tangent.w);
     
     return output;
 }
 
+// ===== ILUMINACIÓN =====
+
+// Luz puntual en la posición del sol, compartida por todos los shaders
+fn sun_direction(world_pos: vec3<f32>) -> vec3<f32> {
+    return normalize(uniforms.light_position - world_pos);
+}
+
+// Caída con el cuadrado de la distancia, menos lo que tapen otros cuerpos
+fn sun_irradiance(world_pos: vec3<f32>) -> f32 {
+    let to_light = uniforms.light_position - world_pos;
+    return uniforms.light_intensity / max(dot(to_light, to_light), 1e-4) * eclipse(world_pos);
+}
+
+// Esferas de los cuerpos de la escena (Occluders en main.rs)
+struct Occluders {
+    spheres: array<vec4<f32>, 16>,
+    count: u32,
+    sun_radius: f32,
+}
+
+@group(0) @binding(3)
+var<uniform> occluders: Occluders;
+
+// Fracción del disco de radio r1 tapada por un disco de radio r2 con los
+// centros a distancia d (área de la lente entre los dos círculos)
+fn disc_overlap(r1: f32, r2: f32, d: f32) -> f32 {
+    if (d >= r1 + r2) {
+        return 0.0;
+    }
+    // Sol puntual: sombra dura
+    if (r1 < 1e-5) {
+        return 1.0;
+    }
+    if (d <= abs(r1 - r2)) {
+        return min(r2 * r2 / (r1 * r1), 1.0);
+    }
+    let a = acos(clamp((d * d + r1 * r1 - r2 * r2) / (2.0 * d * r1), -1.0, 1.0));
+    let b = acos(clamp((d * d + r2 * r2 - r1 * r1) / (2.0 * d * r2), -1.0, 1.0));
+    let area = r1 * r1 * (a - 0.5 * sin(2.0 * a)) + r2 * r2 * (b - 0.5 * sin(2.0 * b));
+    return clamp(area / (3.14159265 * r1 * r1), 0.0, 1.0);
+}
+
+// Parte visible del disco del sol desde el punto: 0 en la umbra, entre 0 y 1
+// en la penumbra. Compara los tamaños aparentes (ángulos) del sol y de cada
+// cuerpo que esté entre el punto y el sol
+fn eclipse(world_pos: vec3<f32>) -> f32 {
+    let to_sun = uniforms.light_position - world_pos;
+    let sun_dist = length(to_sun);
+    let sun_angle = asin(clamp(occluders.sun_radius / max(sun_dist, 1e-4), 0.0, 1.0));
+    
+    var visible = 1.0;
+    for (var i = 0u; i < occluders.count; i++) {
+        let sphere = occluders.spheres[i];
+        if (i == uniforms.body_index || sphere.w <= 0.0) {
+            continue;
+        }
+        let to_body = sphere.xyz - world_pos;
+        let body_dist = length(to_body);
+        // Detrás del punto, más allá del sol o envolviéndolo
+        if (body_dist <= sphere.w || body_dist >= sun_dist || dot(to_body, to_sun) <= 0.0) {
+            continue;
+        }
+        let body_angle = asin(sphere.w / body_dist);
+        let separation = acos(clamp(dot(to_body / body_dist, to_sun / sun_dist), -1.0, 1.0));
+        visible *= 1.0 - disc_overlap(sun_angle, body_angle, separation);
+    }
+    return visible;
+}
+
+// Lambert: n·l por la irradiancia que llega al punto
+fn sun_diffuse(world_pos: vec3<f32>, normal: vec3<f32>) -> f32 {
+    return max(dot(normal, sun_direction(world_pos)), 0.0) * sun_irradiance(world_pos);
+}
+
+// Rugosidad de la superficie que está dibujando el shader del cuerpo. 1.0 (por
+// defecto) marca una superficie mate: sun_specular no suma nada
+var<private> surface_roughness: f32 = 1.0;
+
+// Luz propia de la superficie, aparte de la reflejada: surface_emission brilla
+// siempre (lava) y night_emission sólo del lado de noche (ciudades)
+var<private> surface_emission: vec3<f32> = vec3<f32>(0.0);
+var<private> night_emission: vec3<f32> = vec3<f32>(0.0);
+
+// Luz reflejada en el lado de noche (sin ella queda negro del todo)
+const AMBIENT = 0.04;
+
+// 1 de noche, 0 de día, con una transición suave en el terminador
+fn sun_darkness(world_pos: vec3<f32>, normal: vec3<f32>) -> f32 {
+    return 1.0 - smoothstep(-0.1, 0.2, dot(normal, sun_direction(world_pos)));
+}
+
+// Microfacetas GGX con geometría de Smith (Schlick-GGX), sin Fresnel
+fn ggx_specular(normal: vec3<f32>, view_dir: vec3<f32>, light_dir: vec3<f32>, roughness: f32) -> f32 {
+    let half_dir = normalize(light_dir + view_dir);
+    let n_dot_l = max(dot(normal, light_dir), 0.0);
+    let n_dot_v = max(dot(normal, view_dir), 0.001);
+    let n_dot_h = max(dot(normal, half_dir), 0.0);
+    
+    // Distribución GGX
+    let a2 = roughness * roughness * roughness * roughness;
+    let d = n_dot_h * n_dot_h * (a2 - 1.0) + 1.0;
+    let distribution = a2 / (3.14159265 * d * d);
+    
+    // Geometría de Smith (Schlick-GGX)
+    let k = (roughness + 1.0) * (roughness + 1.0) / 8.0;
+    let geometry = (n_dot_v / (n_dot_v * (1.0 - k) + k)) * (n_dot_l / (n_dot_l * (1.0 - k) + k));
+    
+    return distribution * geometry / (4.0 * n_dot_v * max(n_dot_l, 0.001));
+}
+
+// Fresnel de Schlick
+fn fresnel_schlick(f0: vec3<f32>, view_dir: vec3<f32>, light_dir: vec3<f32>) -> vec3<f32> {
+    let v_dot_h = max(dot(view_dir, normalize(light_dir + view_dir)), 0.0);
+    return f0 + (1.0 - f0) * pow(1.0 - v_dot_h, 5.0);
+}
+
+// Reflejo del sol en superficies dieléctricas (agua, hielo, roca), con la
+// rugosidad que dejó el shader en surface_roughness. GGX con rugosidad 1 no es
+// cero, así que las superficies mates (rugosidad 1 o más) se cortan aquí
+fn sun_specular(world_pos: vec3<f32>, normal: vec3<f32>) -> vec3<f32> {
+    if (surface_roughness >= 1.0) {
+        return vec3<f32>(0.0);
+    }
+    let light_dir = sun_direction(world_pos);
+    let view_dir = uniforms.view_dir;
+    let roughness = clamp(surface_roughness, 0.04, 1.0);
+    let n_dot_l = max(dot(normal, light_dir), 0.0);
+    
+    let fresnel = fresnel_schlick(vec3<f32>(0.02), view_dir, light_dir);
+    return fresnel * ggx_specular(normal, view_dir, light_dir, roughness) * n_dot_l * sun_irradiance(world_pos);
+}
+
+// ===== RELIEVE =====
+
+// Altura del terreno en un punto de la esfera unitaria, con las mismas
+// funciones que usan los shaders de color; 0 para cuerpos sin relieve
+fn terrain_height(pos: vec3<f32>) -> f32 {
+    switch uniforms.shader_type {
+        case 2u: { return rocky_height(pos); }
+        case 6u: { return moon_height(pos); }
+        default: { return 0.0; }
+    }
+}
+
+fn displace(pos: vec3<f32>, normal: vec3<f32>) -> vec3<f32> {
+    return pos + normal * terrain_height(normalize(pos)) * uniforms.displacement;
+}
+
 // ===== FUNCIONES DE RUIDO =====
+// Ruido de valor original; perlin, simplex, fbm, etc. están en noise.wgsl
 
 fn hash(p: vec3<f32>) -> f32 {
-    let h = sin(p.x * 127.1 + p.y * 311.7 + p.z * 74.7) * 43758.5453;
+    let q = p + seed_offset;
+    let h = sin(q.x * 127.1 + q.y * 311.7 + q.z * 74.7) * 43758.5453;
     return fract(h);
 }
 
-fn noise(p: vec3<f32>) -> f32 {
+fn value_noise(p: vec3<f32>) -> f32 {
     let i = floor(p);
     let f = fract(p);
     
@@ -76,14 +298,14 @@ fn noise(p: vec3<f32>) -> f32 {
     return mix(x1, x2, u.y);
 }
 
-fn fbm(p: vec3<f32>, octaves: i32) -> f32 {
+fn value_fbm(p: vec3<f32>, octaves: i32) -> f32 {
     var value = 0.0;
     var amplitude = 0.5;
     var frequency = 1.0;
     var pos = p;
     
     for (var i = 0; i < octaves; i++) {
-        value += amplitude * noise(pos * frequency);
+        value += amplitude * value_noise(pos * frequency);
         frequency *= 2.0;
         amplitude *= 0.5;
     }
@@ -91,179 +313,261 @@ fn fbm(p: vec3<f32>, octaves: i32) -> f32 {
     return value;
 }
 
-fn voronoi(p: vec3<f32>) -> f32 {
-    let pi = floor(p);
-    let pf = fract(p);
-    
-    var min_dist = 2.0;
-    
-    for (var i = -1; i <= 1; i++) {
-        for (var j = -1; j <= 1; j++) {
-            let neighbor = vec3<f32>(f32(i), f32(j), 0.0);
-            let point = neighbor + vec3<f32>(
-                hash(pi + neighbor),
-                hash(pi + neighbor + vec3<f32>(0.1, 0.1, 0.1)),
-                0.0
-            );
-            let diff = point - pf;
-            let dist = dot(diff.xy, diff.xy);
-            min_dist = min(min_dist, dist);
+// ===== PARÁMETROS =====
+
+// Un vec4 por parámetro, en el orden de params.rs
+struct ShaderParams {
+    values: array<vec4<f32>, 16>,
+}
+
+@group(0) @binding(1)
+var<uniform> shader_params: ShaderParams;
+
+fn param(index: i32) -> f32 {
+    return shader_params.values[index].x;
+}
+
+fn param3(index: i32) -> vec3<f32> {
+    return shader_params.values[index].xyz;
+}
+
+const SUN_FREQUENCY = 0;
+const SUN_SPEED = 1;
+const SUN_SWIRL_ARMS = 2;
+const SUN_SPOT_THRESHOLD = 3;
+const SUN_CORONA = 4;
+const SUN_CORONA_SIZE = 5;
+const SUN_LIMB_DARKENING = 6;
+
+const ROCKY_FREQUENCY = 0;
+const ROCKY_OCEAN_LEVEL = 1;
+const ROCKY_MOUNTAIN_LEVEL = 2;
+const ROCKY_SNOW_LEVEL = 3;
+const ROCKY_SNOW_COLOR = 4;
+const ROCKY_MOUNTAIN_COLOR = 5;
+const ROCKY_OCEAN_ROUGHNESS = 6;
+const ROCKY_LAND_ROUGHNESS = 7;
+const ROCKY_CITY_LEVEL = 8;
+const ROCKY_CITY_COLOR = 9;
+
+const GAS_FREQUENCY = 0;
+const GAS_BAND_FREQUENCY = 1;
+const GAS_TURBULENCE = 2;
+const GAS_SPEED = 3;
+const GAS_SPOT_CENTER = 4;
+const GAS_SPOT_RADIUS = 5;
+const GAS_SPOT_COLOR = 6;
+const GAS_SPOT_DARK_COLOR = 7;
+
+const RINGED_FREQUENCY = 0;
+const RINGED_BAND_FREQUENCY = 1;
+const RINGED_RING_INNER = 2;
+const RINGED_RING_OUTER = 3;
+const RINGED_RING_THICKNESS = 4;
+const RINGED_COLOR1 = 5;
+const RINGED_COLOR2 = 6;
+
+const VOLCANIC_FREQUENCY = 0;
+const VOLCANIC_CRACK_WIDTH = 1;
+const VOLCANIC_SPEED = 2;
+const VOLCANIC_PULSE_SPEED = 3;
+const VOLCANIC_GLOW_COLOR = 4;
+
+const MOON_FREQUENCY = 0;
+const MOON_CRATER_SIZE = 1;
+const MOON_MARE_LEVEL = 2;
+const MOON_CRATER_COLOR = 3;
+const MOON_MARE_COLOR = 4;
+
+// ===== RAMPAS DE COLOR =====
+
+// Paradas con el color en xyz y la posición en w, en el orden de ramp.rs
+struct Ramp {
+    stops: array<vec4<f32>, 8>,
+    count: u32,
+    // 0 lineal, 1 smoothstep, 2 constante
+    interpolation: u32,
+}
+
+struct ShaderRamps {
+    ramps: array<Ramp, 4>,
+}
+
+@group(0) @binding(2)
+var<uniform> shader_ramps: ShaderRamps;
+
+// Igual que ColorRamp::sample: fuera de las paradas se queda con el color del extremo
+fn sample_ramp(index: i32, t: f32) -> vec3<f32> {
+    let count = shader_ramps.ramps[index].count;
+    if (count == 0u) {
+        return vec3<f32>(0.0);
+    }
+
+    let first = shader_ramps.ramps[index].stops[0];
+    if (t <= first.w) {
+        return first.xyz;
+    }
+
+    for (var i = 1u; i < count; i++) {
+        let a = shader_ramps.ramps[index].stops[i - 1u];
+        let b = shader_ramps.ramps[index].stops[i];
+        if (t < b.w) {
+            var f = (t - a.w) / max(b.w - a.w, 1e-6);
+            let interpolation = shader_ramps.ramps[index].interpolation;
+            if (interpolation == 1u) {
+                f = f * f * (3.0 - 2.0 * f);
+            } else if (interpolation == 2u) {
+                f = 0.0;
+            }
+            return mix(a.xyz, b.xyz, f);
         }
     }
-    
-    return sqrt(min_dist);
+
+    return shader_ramps.ramps[index].stops[count - 1u].xyz;
 }
 
+const SUN_TEMPERATURE_RAMP = 0;
+
+const ROCKY_OCEAN_RAMP = 0;
+const ROCKY_VEGETATION_RAMP = 1;
+
+const GAS_BANDS_RAMP = 0;
+
+const RINGED_RINGS_RAMP = 0;
+
+const VOLCANIC_LAVA_RAMP = 0;
+const VOLCANIC_ROCK_RAMP = 1;
+
+const MOON_HIGHLANDS_RAMP = 0;
+
 // ===== SHADERS DE PLANETAS =====
 
 // SHADER 1: SOL
-fn sun_shader(pos: vec3<f32>, time: f32) -> vec3<f32> {
-    let p = pos * 3.0;
+fn sun_shader(pos: vec3<f32>, time_in: f32) -> vec3<f32> {
+    let p = pos * param(SUN_FREQUENCY);
+    let time = time_in * param(SUN_SPEED);
     
     // Plasma multicapa
-    let plasma1 = fbm(p + vec3<f32>(time, time * 0.5, 0.0), 4);
-    let plasma2 = fbm(p * 2.0 - vec3<f32>(time * 0.7, time * 1.1, time * 0.3), 3);
-    let plasma3 = fbm(p * 0.5 + vec3<f32>(cos(time * 0.5), sin(time * 0.5), time * 0.2), 3);
+    let plasma1 = value_fbm(p + vec3<f32>(time, time * 0.5, 0.0), 4);
+    let plasma2 = value_fbm(p * 2.0 - vec3<f32>(time * 0.7, time * 1.1, time * 0.3), 3);
+    let plasma3 = value_fbm(p * 0.5 + vec3<f32>(cos(time * 0.5), sin(time * 0.5), time * 0.2), 3);
     
     let combined = clamp((plasma1 * 0.5 + plasma2 * 0.3 + plasma3 * 0.2), 0.0, 1.0);
     
     // Vórtices
     let angle = atan2(p.y, p.x);
     let radius = length(p.xy);
-    let swirl = sin(angle * 6.0 + radius * 4.0 - time * 2.0 + combined * 2.0) * 0.5 + 0.5;
+    let swirl = sin(angle * param(SUN_SWIRL_ARMS) + radius * 4.0 - time * 2.0 + combined * 2.0) * 0.5 + 0.5;
     
     // Manchas solares
-    let spot_noise = fbm(p * 3.0 + vec3<f32>(time * 0.05, 0.0, 0.0), 3);
-    let spots = select(1.0, 0.4, spot_noise > 0.68);
-    
-    // Corona pulsante
-    let dist = length(p);
-    let corona = pow(max(1.0 - dist * 0.4, 0.0), 2.5);
-    let pulse = sin(time * 3.0) * 0.15 + 0.85;
+    let spot_noise = value_fbm(p * 3.0 + vec3<f32>(time * 0.05, 0.0, 0.0), 3);
+    let spots = select(1.0, 0.4, spot_noise > param(SUN_SPOT_THRESHOLD));
     
-    // Gradiente de temperatura
+    // Gradiente de temperatura (la corona va aparte, en fs_corona)
     let temp = combined * swirl * 1.2;
-    var base_color: vec3<f32>;
-    
-    if (temp > 0.75) {
-        base_color = vec3<f32>(1.0, 1.0, 0.98);
-    } else if (temp > 0.55) {
-        base_color = vec3<f32>(1.0, 0.96, 0.78);
-    } else if (temp > 0.35) {
-        base_color = vec3<f32>(1.0, 0.78, 0.39);
-    } else {
-        base_color = vec3<f32>(1.0, 0.55, 0.20);
-    }
+    let base_color = sample_ramp(SUN_TEMPERATURE_RAMP, temp);
     
-    return base_color * spots * (1.0 + corona * pulse * 0.8);
+    return base_color * spots;
+}
+
+// Oscurecimiento del borde: hacia el limbo se ve gas más alto y más frío. mu es
+// el coseno entre la normal y la dirección al observador; el azul se oscurece
+// más que el rojo, así que el borde queda más anaranjado
+fn limb_darkening(normal: vec3<f32>) -> vec3<f32> {
+    let mu = clamp(dot(normal, uniforms.view_dir), 0.0, 1.0);
+    let u = param(SUN_LIMB_DARKENING) * vec3<f32>(0.85, 1.0, 1.2);
+    return max(vec3<f32>(1.0) - u * (1.0 - mu), vec3<f32>(0.0));
 }
 
 // SHADER 2: PLANETA ROCOSO (TIERRA)
-fn rocky_planet_shader(pos: vec3<f32>, time: f32) -> vec3<f32> {
-    let p = pos * 5.0;
-    
-    // Continentes con Voronoi
-    let continents = voronoi(p * 0.8);
-    let mountains = fbm(p * 3.0, 2) * 0.3;
-    let terrain_height = continents * 0.7 + mountains * 0.3;
+fn rocky_terrain(p: vec3<f32>) -> f32 {
+    // Continentes con Voronoi; cada placa sube o baja un poco según su id
+    let plates = worley(p * 0.8);
+    let continents = plates.f1 + (unit_float(plates.id) - 0.5) * 0.1;
+    let mountains = value_fbm(p * 3.0, 2) * 0.3;
+    return continents * 0.7 + mountains * 0.3;
+}
+
+// Los océanos quedan planos al nivel del mar
+fn rocky_height(pos: vec3<f32>) -> f32 {
+    return max(rocky_terrain(pos * param(ROCKY_FREQUENCY)) - param(ROCKY_OCEAN_LEVEL), 0.0);
+}
+
+fn rocky_planet_shader(pos: vec3<f32>) -> vec3<f32> {
+    let p = pos * param(ROCKY_FREQUENCY);
+    let terrain_height = rocky_terrain(p);
+    let ocean_level = param(ROCKY_OCEAN_LEVEL);
     
     var color: vec3<f32>;
     
-    if (terrain_height < 0.35) {
-        // Océanos
-        let depth = (0.35 - terrain_height) * 5.0;
-        if (depth > 0.6) {
-            color = vec3<f32>(0.04, 0.16, 0.39); // Océano profundo
-        } else {
-            color = vec3<f32>(0.12, 0.31, 0.63); // Océano normal
-        }
-    } else if (terrain_height >= 0.65) {
+    if (terrain_height < ocean_level) {
+        // Océanos: el agua es lisa y refleja el sol
+        let depth = (ocean_level - terrain_height) * 5.0;
+        color = sample_ramp(ROCKY_OCEAN_RAMP, depth);
+        surface_roughness = param(ROCKY_OCEAN_ROUGHNESS);
+    } else if (terrain_height >= param(ROCKY_SNOW_LEVEL)) {
         // Nieve
-        color = vec3<f32>(0.98, 0.98, 1.0);
-    } else if (terrain_height >= 0.55) {
+        color = param3(ROCKY_SNOW_COLOR);
+    } else if (terrain_height >= param(ROCKY_MOUNTAIN_LEVEL)) {
         // Montañas
-        color = vec3<f32>(0.51, 0.43, 0.35);
+        color = param3(ROCKY_MOUNTAIN_COLOR);
     } else {
         // Vegetación
-        let veg = fbm(p * 5.0, 2);
-        if (veg > 0.6) {
-            color = vec3<f32>(0.20, 0.55, 0.20); // Bosques
-        } else if (veg > 0.4) {
-            color = vec3<f32>(0.39, 0.63, 0.27); // Pastizales
-        } else {
-            color = vec3<f32>(0.82, 0.75, 0.55); // Desiertos
-        }
+        let veg = value_fbm(p * 5.0, 2);
+        color = sample_ramp(ROCKY_VEGETATION_RAMP, veg);
     }
-    
-    // Nubes dinámicas
-    let cloud1 = fbm(p * 2.0 + vec3<f32>(time * 15.0, 0.0, time * 8.0), 3);
-    let cloud2 = fbm(p * 4.0 - vec3<f32>(time * 10.0, 0.0, time * 5.0), 2);
-    let clouds = clamp((cloud1 * 0.7 + cloud2 * 0.3), 0.0, 1.0);
-    
-    if (clouds > 0.6) {
-        let density = min((clouds - 0.6) / 0.4, 1.0);
-        color = mix(color, vec3<f32>(1.0, 1.0, 1.0), density * 0.85);
+    if (terrain_height >= ocean_level) {
+        surface_roughness = param(ROCKY_LAND_ROUGHNESS);
     }
     
-    // Atmósfera azul
-    let dist = length(p);
-    let atmosphere = pow(max(1.0 - dist * 0.2, 0.0), 5.0);
-    if (atmosphere > 0.0) {
-        color = mix(color, vec3<f32>(0.39, 0.59, 1.0), atmosphere * 0.4);
+    // Luces de ciudades en la tierra baja: manchas de población con puntos finos
+    if (terrain_height >= ocean_level && terrain_height < param(ROCKY_MOUNTAIN_LEVEL)) {
+        let population = value_fbm(p * 6.0 + vec3<f32>(3.1, 7.4, 1.9), 3);
+        let level = param(ROCKY_CITY_LEVEL);
+        let density = smoothstep(level, level + 0.15, population);
+        let lights = step(0.55, value_noise(p * 80.0));
+        night_emission = param3(ROCKY_CITY_COLOR) * density * lights;
     }
     
     return color;
 }
 
 // SHADER 3: GIGANTE GASEOSO (JÚPITER)
-fn gas_giant_shader(pos: vec3<f32>, time: f32) -> vec3<f32> {
-    let p = pos * 3.5;
+fn gas_giant_shader(pos: vec3<f32>, time_in: f32) -> vec3<f32> {
+    let p = pos * param(GAS_FREQUENCY);
+    let time = time_in * param(GAS_SPEED);
     
     // Bandas horizontales con turbulencia
-    let base_bands = p.y * 18.0;
-    let turb1 = fbm(p * 2.0 + vec3<f32>(time * 1.5, 0.0, 0.0), 3) * 2.0;
-    let turb2 = fbm(p * 4.0 - vec3<f32>(time * 0.8, 0.0, time * 0.5), 2) * 0.8;
+    let base_bands = p.y * param(GAS_BAND_FREQUENCY);
+    let turb1 = value_fbm(p * 2.0 + vec3<f32>(time * 1.5, 0.0, 0.0), 3) * 2.0;
+    let turb2 = value_fbm(p * 4.0 - vec3<f32>(time * 0.8, 0.0, time * 0.5), 2) * 0.8;
     
-    let band_pos = base_bands + turb1 + turb2;
+    let band_pos = base_bands + (turb1 + turb2) * param(GAS_TURBULENCE);
     let bands = sin(band_pos) * 0.5 + 0.5;
     
-    let atmosphere_chaos = fbm(p * 3.0 + vec3<f32>(time, 0.0, 0.0), 3);
+    let atmosphere_chaos = value_fbm(p * 3.0 + vec3<f32>(time, 0.0, 0.0), 3);
     let band_value = clamp(bands * 0.6 + atmosphere_chaos * 0.4, 0.0, 1.0);
     
     // Paleta joviana
-    let color1 = vec3<f32>(0.98, 0.90, 0.75);
-    let color2 = vec3<f32>(0.67, 0.47, 0.31);
-    let color3 = vec3<f32>(0.82, 0.71, 0.55);
-    let color4 = vec3<f32>(1.0, 0.96, 0.86);
-    
-    var final_color: vec3<f32>;
-    
-    if (band_value < 0.25) {
-        final_color = mix(color1, color2, band_value * 4.0);
-    } else if (band_value < 0.5) {
-        final_color = mix(color2, color3, (band_value - 0.25) * 4.0);
-    } else if (band_value < 0.75) {
-        final_color = mix(color3, color4, (band_value - 0.5) * 4.0);
-    } else {
-        final_color = mix(color4, color1, (band_value - 0.75) * 4.0);
-    }
+    var final_color = sample_ramp(GAS_BANDS_RAMP, band_value);
     
     // Gran Mancha Roja
-    let spot_center = vec3<f32>(0.6, -0.3, 0.0);
+    let spot_center = param3(GAS_SPOT_CENTER);
+    let spot_radius = param(GAS_SPOT_RADIUS);
     let dx = p.x - spot_center.x;
     let dy = (p.y - spot_center.y) * 1.4;
     let dz = p.z - spot_center.z;
     let dist_to_spot = sqrt(dx * dx + dy * dy + dz * dz);
     
-    if (dist_to_spot < 0.5) {
-        let spot_factor = max(1.0 - dist_to_spot / 0.5, 0.0);
+    if (dist_to_spot < spot_radius) {
+        let spot_factor = max(1.0 - dist_to_spot / spot_radius, 0.0);
         let angle = atan2(dy, dx);
         let swirl = sin(angle * 5.0 + dist_to_spot * 15.0 - time * 3.0) * 0.5 + 0.5;
         
         let red_intensity = spot_factor * (0.7 + swirl * 0.3);
         let red_color = select(
-            vec3<f32>(0.75, 0.24, 0.16),
-            vec3<f32>(0.94, 0.39, 0.27),
+            param3(GAS_SPOT_DARK_COLOR),
+            param3(GAS_SPOT_COLOR),
             swirl > 0.6
         );
         
@@ -275,19 +579,19 @@ fn gas_giant_shader(pos: vec3<f32>, time: f32) -> vec3<f32> {
 
 // SHADER 4: SATURNO CON ANILLOS
 fn ringed_planet_shader(pos: vec3<f32>, time: f32) -> vec3<f32> {
-    let p = pos * 3.0;
+    let p = pos * param(RINGED_FREQUENCY);
     
     // Planeta base
-    let bands = sin(p.y * 20.0 + fbm(p, 2) * 0.5) * 0.5 + 0.5;
-    let color1 = vec3<f32>(1.0, 0.94, 0.82);
-    let color2 = vec3<f32>(0.94, 0.86, 0.69);
-    var planet_color = mix(color1, color2, bands);
+    let bands = sin(p.y * param(RINGED_BAND_FREQUENCY) + value_fbm(p, 2) * 0.5) * 0.5 + 0.5;
+    var planet_color = mix(param3(RINGED_COLOR1), param3(RINGED_COLOR2), bands);
     
     // ANILLOS ESPECTACULARES
     let ring_dist = length(p.xz);
     let y_abs = abs(p.y);
+    let ring_thickness = param(RINGED_RING_THICKNESS);
+    let ring_inner = param(RINGED_RING_INNER);
     
-    if (y_abs < 0.18 && ring_dist > 0.75 && ring_dist < 2.0) {
+    if (y_abs < ring_thickness && ring_dist > ring_inner && ring_dist < param(RINGED_RING_OUTER)) {
         let ring_freq = ring_dist * 50.0;
         let ring_bands = sin(ring_freq) * 0.5 + 0.5;
         let brightness_var = sin(ring_dist * 30.0 + time * 3.0) * 0.5 + 0.5;
@@ -300,16 +604,9 @@ fn ringed_planet_shader(pos: vec3<f32>, time: f32) -> vec3<f32> {
         if (is_gap) {
             planet_color *= 0.4;
         } else {
-            var ring_color: vec3<f32>;
-            if (ring_bands > 0.7) {
-                ring_color = vec3<f32>(0.96, 0.88, 0.75);
-            } else if (ring_bands > 0.4) {
-                ring_color = vec3<f32>(0.82, 0.73, 0.57);
-            } else {
-                ring_color = vec3<f32>(0.71, 0.63, 0.49);
-            }
+            let ring_color = sample_ramp(RINGED_RINGS_RAMP, ring_bands);
             
-            let ring_alpha = (1.0 - pow(y_abs / 0.18, 1.2)) * 0.95;
+            let ring_alpha = (1.0 - pow(y_abs / ring_thickness, 1.2)) * 0.95;
             let ring_bright = 0.9 + brightness_var * 0.2;
             
             planet_color = mix(planet_color, ring_color, ring_alpha);
@@ -318,7 +615,7 @@ fn ringed_planet_shader(pos: vec3<f32>, time: f32) -> vec3<f32> {
     }
     
     // Sombra de anillos en el planeta
-    if (y_abs < 0.2 && ring_dist < 0.9) {
+    if (y_abs < ring_thickness + 0.02 && ring_dist < ring_inner + 0.15) {
         let shadow_bands = sin(ring_dist * 50.0) * 0.5 + 0.5;
         let shadow = 0.6 + shadow_bands * 0.3;
         planet_color *= shadow;
@@ -328,90 +625,105 @@ fn ringed_planet_shader(pos: vec3<f32>, time: f32) -> vec3<f32> {
 }
 
 // SHADER 5: PLANETA VOLCÁNICO
-fn volcanic_planet_shader(pos: vec3<f32>, time: f32) -> vec3<f32> {
-    let p = pos * 4.0;
+fn volcanic_planet_shader(pos: vec3<f32>, time_in: f32) -> vec3<f32> {
+    let p = pos * param(VOLCANIC_FREQUENCY);
+    let time = time_in * param(VOLCANIC_SPEED);
     
-    // Superficie con Voronoi para grietas
-    let cracks = voronoi(p * 1.5);
-    let fine_cracks = fbm(p * 8.0 + vec3<f32>(time, 0.0, 0.0), 3);
+    // Red de grietas en los bordes de las celdas de Worley
+    let cracks = worley(p * 1.5).edge;
+    let fine_cracks = value_fbm(p * 8.0 + vec3<f32>(time, 0.0, 0.0), 3);
     
-    let is_lava = cracks < 0.4 || fine_cracks > 0.8;
+    let is_lava = cracks < param(VOLCANIC_CRACK_WIDTH) || fine_cracks > 0.8;
     
-    var color: vec3<f32>;
+    // La roca refleja la luz del sol; la lava brilla por sí misma, también de noche
+    let rock_var = value_fbm(p * 10.0, 2);
+    var color = sample_ramp(VOLCANIC_ROCK_RAMP, rock_var);
     
     if (is_lava) {
-        let heat = fbm(p * 2.0 + vec3<f32>(time * 2.0, 0.0, time), 3);
-        let pulse = sin(time * 5.0) * 0.25 + 0.75;
-        
-        if (heat > 0.75) {
-            color = vec3<f32>(1.0, 1.0, 0.86) * pulse;
-        } else if (heat > 0.55) {
-            color = vec3<f32>(1.0, 0.90, 0.47) * pulse;
-        } else if (heat > 0.35) {
-            color = vec3<f32>(1.0, 0.59, 0.20) * pulse;
-        } else {
-            color = vec3<f32>(0.86, 0.27, 0.12) * pulse;
-        }
-    } else {
-        let rock_var = fbm(p * 10.0, 2);
-        if (rock_var > 0.6) {
-            color = vec3<f32>(0.27, 0.24, 0.22);
-        } else {
-            color = vec3<f32>(0.14, 0.12, 0.10);
-        }
+        let heat = value_fbm(p * 2.0 + vec3<f32>(time * 2.0, 0.0, time), 3);
+        let pulse = sin(time_in * param(VOLCANIC_PULSE_SPEED)) * 0.25 + 0.75;
+        color = sample_ramp(VOLCANIC_ROCK_RAMP, 0.0);
+        surface_emission = sample_ramp(VOLCANIC_LAVA_RAMP, heat) * pulse;
     }
     
     // Grietas ultra brillantes
     if (fine_cracks > 0.88) {
         let glow = (fine_cracks - 0.88) / 0.12;
-        color = mix(color, vec3<f32>(1.0, 0.86, 0.39), glow);
+        surface_emission = mix(surface_emission, param3(VOLCANIC_GLOW_COLOR), glow);
     }
     
     // Resplandor ambiental
-    let ambient = fbm(p * 1.5 - vec3<f32>(time * 0.5, 0.0, 0.0), 2) * 0.3;
-    color += vec3<f32>(ambient * 0.8, ambient * 0.4, 0.0);
+    let ambient = value_fbm(p * 1.5 - vec3<f32>(time * 0.5, 0.0, 0.0), 2) * 0.3;
+    surface_emission += vec3<f32>(ambient * 0.8, ambient * 0.4, 0.0);
     
     return color;
 }
 
 // SHADER 6: LUNA
+// Cráteres hundidos y tierras altas elevadas
+fn moon_height(pos: vec3<f32>) -> f32 {
+    let p = pos * param(MOON_FREQUENCY);
+    let crater_depth = max(param(MOON_CRATER_SIZE) - worley(p * 1.2).f1, 0.0) * 2.0;
+    let highland = value_fbm(p * 2.0, 2);
+    return highland * 0.3 - crater_depth;
+}
+
 fn moon_shader(pos: vec3<f32>) -> vec3<f32> {
-    let p = pos * 5.0;
+    let p = pos * param(MOON_FREQUENCY);
     
     // Cráteres con Voronoi
-    let crater_pattern = voronoi(p * 1.2);
-    let is_crater = crater_pattern < 0.25;
+    let crater_pattern = worley(p * 1.2).f1;
+    let is_crater = crater_pattern < param(MOON_CRATER_SIZE);
     
     // Mares lunares
-    let mare_pattern = fbm(p * 0.8, 3);
-    let is_mare = mare_pattern < 0.3;
+    let mare_pattern = value_fbm(p * 0.8, 3);
+    let is_mare = mare_pattern < param(MOON_MARE_LEVEL);
     
     // Tierras altas
-    let highland = fbm(p * 2.0, 2);
-    let is_highland = highland > 0.7;
+    let highland = value_fbm(p * 2.0, 2);
     
     var color: vec3<f32>;
     
     if (is_crater) {
-        color = vec3<f32>(0.24, 0.24, 0.24);
+        color = param3(MOON_CRATER_COLOR);
     } else if (is_mare) {
-        color = vec3<f32>(0.31, 0.31, 0.31);
-    } else if (is_highland) {
-        color = vec3<f32>(0.75, 0.75, 0.75);
+        color = param3(MOON_MARE_COLOR);
     } else {
-        color = vec3<f32>(0.55, 0.55, 0.55);
+        color = sample_ramp(MOON_HIGHLANDS_RAMP, highland);
     }
     
     // Detalle fino
-    let fine_detail = fbm(p * 15.0, 2);
+    let fine_detail = value_fbm(p * 15.0, 2);
     color *= (0.90 + fine_detail * 0.20);
     
     return color;
 }
 
+// SHADER 7: MATERIAL PBR METALLIC-ROUGHNESS (modelos glTF)
+fn pbr_shader(normal: vec3<f32>, world_pos: vec3<f32>) -> vec3<f32> {
+    let light_dir = sun_direction(world_pos);
+    let view_dir = uniforms.view_dir;
+    
+    let albedo = uniforms.base_color.rgb;
+    let metallic = clamp(uniforms.metallic, 0.0, 1.0);
+    let roughness = clamp(uniforms.roughness, 0.04, 1.0);
+    
+    let n_dot_l = max(dot(normal, light_dir), 0.0);
+    
+    let f0 = mix(vec3<f32>(0.04), albedo, metallic);
+    let fresnel = fresnel_schlick(f0, view_dir, light_dir);
+    
+    let specular = fresnel * ggx_specular(normal, view_dir, light_dir, roughness);
+    let diffuse = (1.0 - fresnel) * (1.0 - metallic) * albedo / 3.14159265;
+    
+    let ambient = albedo * 0.15;
+    return ambient + (diffuse + specular) * n_dot_l * sun_irradiance(world_pos) * 3.0 + uniforms.emissive;
+}
+
 @fragment
 fn fs_main(input: VertexOutput) -> @location(0) vec4<f32> {
-    let pos = normalize(input.world_pos);
+    set_noise_seed(uniforms.seed);
+    let pos = normalize(input.local_pos);
     let normal = normalize(input.normal);
     let time = uniforms.time;
     
@@ -420,20 +732,422 @@ fn fs_main(input: VertexOutput) -> @location(0) vec4<f32> {
     // Seleccionar shader según tipo
     switch uniforms.shader_type {
         case 1u: { color = sun_shader(pos, time); }
-        case 2u: { color = rocky_planet_shader(pos, time); }
+        case 2u: { color = rocky_planet_shader(pos); }
         case 3u: { color = gas_giant_shader(pos, time); }
         case 4u: { color = ringed_planet_shader(pos, time); }
         case 5u: { color = volcanic_planet_shader(pos, time); }
         case 6u: { color = moon_shader(pos); }
+        // El material PBR ya incluye su propia iluminación
+        case 7u: { return vec4<f32>(pbr_shader(normal, input.world_pos), 1.0); }
         default: { color = vec3<f32>(1.0, 0.0, 1.0); }
     }
     
-    // Iluminación básica
-    let light_dir = normalize(vec3<f32>(1.0, 0.5, 0.8));
-    let diffuse = max(dot(normal, light_dir), 0.15);
+    // El sol emite su propia luz
+    if (uniforms.shader_type == 1u) {
+        return vec4<f32>(color * limb_darkening(normal), 1.0);
+    }
+    
+    // Luz reflejada del sol, con la sombra de las nubes
+    let shadow = cloud_shadow(pos, input.world_pos);
+    var lit = color * (AMBIENT + sun_diffuse(input.world_pos, normal) * shadow);
+    lit += sun_specular(input.world_pos, normal) * shadow;
+    
+    // Luz propia, que no depende del sol
+    lit += surface_emission + night_emission * sun_darkness(input.world_pos, normal);
+    
+    return vec4<f32>(lit, 1.0);
+}
+
+// ===== NUBES =====
+// Esfera translúcida un poco más grande que el cuerpo, que gira a su propio
+// ritmo; se dibuja después de los cuerpos y hace sombra sobre la superficie
+
+// Giro sobre el eje y, en el mismo sentido que Spin::rotation
+fn rotate_y(v: vec3<f32>, angle: f32) -> vec3<f32> {
+    let c = cos(angle);
+    let s = sin(angle);
+    return vec3<f32>(c * v.x + s * v.z, v.y, -s * v.x + c * v.z);
+}
+
+// Direcciones del marco del cuerpo al mundo y al revés (la escala del cuerpo
+// es uniforme, así que basta con normalizar)
+fn body_to_world(v: vec3<f32>) -> vec3<f32> {
+    return normalize((uniforms.model_matrix * vec4<f32>(v, 0.0)).xyz);
+}
+
+fn world_to_body(v: vec3<f32>) -> vec3<f32> {
+    let m = uniforms.model_matrix;
+    return normalize(transpose(mat3x3<f32>(m[0].xyz, m[1].xyz, m[2].xyz)) * v);
+}
+
+// Lo que las nubes adelantan al cuerpo, que gira sobre el mismo eje
+fn cloud_angle() -> f32 {
+    return uniforms.spin_angle * (uniforms.cloud_speed - 1.0);
+}
+
+// Densidad en [0, 1] en un punto de la esfera unitaria, en el marco de las nubes
+fn cloud_density(p: vec3<f32>) -> f32 {
+    let q = p * 5.0;
+    let drift = uniforms.time * 0.05;
+    let cloud1 = value_fbm(q * 2.0 + vec3<f32>(drift * 1.5, 0.0, drift * 0.8), 3);
+    let cloud2 = value_fbm(q * 4.0 - vec3<f32>(drift, 0.0, drift * 0.5), 2);
+    let clouds = clamp(cloud1 * 0.7 + cloud2 * 0.3, 0.0, 1.0);
+    let cover = uniforms.cloud_cover;
+    return clamp((clouds - cover) / max(1.0 - cover, 1e-3), 0.0, 1.0);
+}
+
+// Luz del sol que dejan pasar las nubes hasta el suelo: se busca la nube que
+// está entre el punto y el sol, desplazada según la altura de la capa. ground
+// es la dirección del punto en el marco del cuerpo
+fn cloud_shadow(ground: vec3<f32>, world_pos: vec3<f32>) -> f32 {
+    if (uniforms.cloud_altitude <= 0.0) {
+        return 1.0;
+    }
+    let light_dir = sun_direction(world_pos);
+    let up = body_to_world(ground);
+    let slant = uniforms.cloud_altitude / max(dot(up, light_dir), 0.15);
+    let above = rotate_y(world_to_body(up + light_dir * slant), -cloud_angle());
+    return 1.0 - cloud_density(above) * 0.6;
+}
+
+@vertex
+fn vs_clouds(input: VertexInput) -> VertexOutput {
+    var output: VertexOutput;
+    
+    let radius = uniforms.planet_scale * (1.0 + uniforms.cloud_altitude);
+    let dir = normalize(input.position);
+    // La capa gira con el cuerpo (mismo eje inclinado) y un poco más
+    let up = body_to_world(rotate_y(dir, cloud_angle()));
+    let world_pos = body_center() + up * radius;
+    
+    output.clip_position = project(world_pos);
+    output.world_pos = world_pos;
+    output.normal = up;
+    output.tangent = input.tangent;
+    // Marco de las nubes: el patrón gira con la capa
+    output.local_pos = dir;
+    
+    return output;
+}
+
+@fragment
+fn fs_clouds(input: VertexOutput) -> @location(0) vec4<f32> {
+    set_noise_seed(uniforms.seed);
+    let density = cloud_density(normalize(input.local_pos));
+    if (density <= 0.0) {
+        discard;
+    }
+    
+    let light = AMBIENT + sun_diffuse(input.world_pos, normalize(input.normal));
+    let alpha = density * 0.85;
+    return vec4<f32>(vec3<f32>(light * alpha), alpha);
+}
+
+// ===== ATMÓSFERA =====
+// Capa esférica alrededor del cuerpo, dibujada después de los cuerpos con
+// mezcla: color = luz dispersada + fondo * transmitancia. Dispersión simple de
+// Rayleigh (azul) y Mie (halo blanco hacia el sol) integrada a lo largo del rayo
+
+// Coeficientes por unidad de radio del planeta, así el aspecto no depende del tamaño
+const RAYLEIGH = vec3<f32>(0.7, 1.63, 4.0);
+const MIE = 0.8;
+// Altura de escala como fracción del espesor de la atmósfera
+const RAYLEIGH_HEIGHT = 0.25;
+const MIE_HEIGHT = 0.1;
+// Asimetría de Henyey-Greenstein: la bruma dispersa sobre todo hacia adelante
+const MIE_G = 0.76;
+const SCATTER_INTENSITY = 30.0;
+const VIEW_SAMPLES = 16;
+const LIGHT_SAMPLES = 4;
+
+@vertex
+fn vs_atmosphere(input: VertexInput) -> VertexOutput {
+    var output: VertexOutput;
+    
+    // Esfera unitaria un poco más grande que el cuerpo, con su misma matriz de modelo
+    let dir = normalize(input.position);
+    let world_pos = (uniforms.model_matrix * vec4<f32>(dir * (1.0 + uniforms.atmosphere), 1.0)).xyz;
+    
+    output.clip_position = project(world_pos);
+    output.world_pos = world_pos;
+    output.normal = normalize((uniforms.normal_matrix * vec4<f32>(dir, 0.0)).xyz);
+    output.tangent = input.tangent;
+    output.local_pos = dir * uniforms.planet_scale * (1.0 + uniforms.atmosphere);
+    
+    return output;
+}
+
+// Distancias de entrada y salida del rayo en una esfera centrada en el origen;
+// x > y si no la toca
+fn ray_sphere(origin: vec3<f32>, dir: vec3<f32>, radius: f32) -> vec2<f32> {
+    let b = dot(origin, dir);
+    let c = dot(origin, origin) - radius * radius;
+    let h = b * b - c;
+    if (h < 0.0) {
+        return vec2<f32>(1.0, -1.0);
+    }
+    let root = sqrt(h);
+    return vec2<f32>(-b - root, -b + root);
+}
+
+// Densidad de Rayleigh (x) y Mie (y) a una altura normalizada en [0, 1]
+fn atmosphere_density(p: vec3<f32>) -> vec2<f32> {
+    let radius = uniforms.planet_scale;
+    let height = (length(p) - radius) / (radius * uniforms.atmosphere);
+    return exp(-max(height, 0.0) / vec2<f32>(RAYLEIGH_HEIGHT, MIE_HEIGHT));
+}
+
+@fragment
+fn fs_atmosphere(input: VertexOutput) -> @location(0) vec4<f32> {
+    let radius = uniforms.planet_scale;
+    let outer = radius * (1.0 + uniforms.atmosphere);
+    let center = body_center();
+    
+    // La proyección es ortográfica: todos los rayos de vista son paralelos.
+    // Empiezan delante de la capa, sobre la recta que pasa por el fragmento
+    let dir = -uniforms.view_dir;
+    let origin = input.world_pos - center + uniforms.view_dir * (2.0 * outer);
+    
+    let shell = ray_sphere(origin, dir, outer);
+    if (shell.x > shell.y) {
+        discard;
+    }
+    var t_end = shell.y;
+    let ground = ray_sphere(origin, dir, radius);
+    if (ground.x <= ground.y) {
+        t_end = min(t_end, ground.x);
+    }
+    
+    let light_dir = normalize(uniforms.light_position - center);
+    let step_size = (t_end - shell.x) / f32(VIEW_SAMPLES);
+    
+    var view_depth = vec2<f32>(0.0);
+    var rayleigh_sum = vec3<f32>(0.0);
+    var mie_sum = vec3<f32>(0.0);
+    
+    for (var i = 0; i < VIEW_SAMPLES; i++) {
+        let p = origin + dir * (shell.x + step_size * (f32(i) + 0.5));
+        let density = atmosphere_density(p) * step_size / radius;
+        view_depth += density;
+        
+        // Sin luz del sol en la sombra del planeta
+        let blocker = ray_sphere(p, light_dir, radius);
+        if (blocker.x <= blocker.y && blocker.x > 0.0) {
+            continue;
+        }
+        
+        // Profundidad óptica hacia el sol: la luz llega enrojecida cerca del terminador
+        let light_step = ray_sphere(p, light_dir, outer).y / f32(LIGHT_SAMPLES);
+        var light_depth = vec2<f32>(0.0);
+        for (var j = 0; j < LIGHT_SAMPLES; j++) {
+            let q = p + light_dir * (light_step * (f32(j) + 0.5));
+            light_depth += atmosphere_density(q) * light_step / radius;
+        }
+        
+        let depth = view_depth + light_depth;
+        let attenuation = exp(-(RAYLEIGH * depth.x + MIE * 1.1 * depth.y));
+        rayleigh_sum += density.x * attenuation;
+        mie_sum += density.y * attenuation;
+    }
+    
+    // Funciones de fase
+    let mu = dot(dir, light_dir);
+    let rayleigh_phase = 3.0 / (16.0 * 3.14159265) * (1.0 + mu * mu);
+    let g2 = MIE_G * MIE_G;
+    let mie_phase = (1.0 - g2) / (4.0 * 3.14159265 * pow(1.0 + g2 - 2.0 * MIE_G * mu, 1.5));
     
-    // Auto-emisión para sol y lava
-    let emission = select(1.0, diffuse, uniforms.shader_type != 1u && uniforms.shader_type != 5u);
+    let irradiance = sun_irradiance(center) * SCATTER_INTENSITY;
+    let color = irradiance * (rayleigh_sum * RAYLEIGH * rayleigh_phase + mie_sum * MIE * mie_phase);
+    let transmittance = exp(-(RAYLEIGH * view_depth.x + MIE * 1.1 * view_depth.y));
     
-    return vec4<f32>(color * mix(1.0, diffuse, 0.7), 1.0);
+    return vec4<f32>(color, 1.0 - (transmittance.r + transmittance.g + transmittance.b) / 3.0);
+}
+
+// ===== CORONA =====
+// Cuadrado de cara a la cámara alrededor de cada sol, en el plano de su centro:
+// el disco del sol y los cuerpos de delante la tapan con la profundidad. Se
+// suma al color que ya hay (mezcla aditiva)
+
+@vertex
+fn vs_corona(input: VertexInput) -> VertexOutput {
+    var output: VertexOutput;
+    
+    // Esquinas del cuadrado en [-1, 1], estiradas hasta el borde de la corona
+    // alrededor del centro del sol, en el plano de la pantalla
+    let corner = input.position.xy * param(SUN_CORONA_SIZE);
+    let world_pos = body_center() + screen_axes() * (corner * uniforms.planet_scale);
+    
+    output.clip_position = project(world_pos);
+    output.world_pos = world_pos;
+    output.normal = vec3<f32>(0.0, 0.0, 1.0);
+    output.tangent = input.tangent;
+    // En radios del sol
+    output.local_pos = vec3<f32>(corner, 0.0);
+    
+    return output;
+}
+
+@fragment
+fn fs_corona(input: VertexOutput) -> @location(0) vec4<f32> {
+    set_noise_seed(uniforms.seed);
+    let r = length(input.local_pos.xy);
+    let size = param(SUN_CORONA_SIZE);
+    if (r < 1.0 || r > size) {
+        discard;
+    }
+    
+    let time = uniforms.time * param(SUN_SPEED);
+    let dir = input.local_pos.xy / r;
+    
+    // Brillo que cae rápido con la distancia y se apaga antes del borde del cuadrado
+    let falloff = pow(r, -3.0) * (1.0 - smoothstep(size * 0.6, size, r));
+    
+    // Serpentinas: ruido que varía mucho con el ángulo y poco con el radio, así
+    // que queda estirado hacia afuera; r en el dominio lo hace fluir hacia afuera
+    let streamer_noise = value_fbm(vec3<f32>(dir * 4.0, r * 0.4 - time * 0.15), 4);
+    let streamers = pow(clamp(streamer_noise, 0.0, 1.0), 2.0);
+    let pulse = sin(time * 3.0) * 0.15 + 0.85;
+    
+    let intensity = falloff * (0.35 + streamers * 2.0) * pulse * param(SUN_CORONA);
+    let color = sample_ramp(SUN_TEMPERATURE_RAMP, 0.75 - streamers * 0.3);
+    return vec4<f32>(color * intensity, 0.0);
+}
+
+// ===== CIELO =====
+// Fondo que se dibuja antes que los cuerpos: la Vía Láctea y las nebulosas en
+// un cuadrado que cubre la pantalla (fs_sky) y las estrellas del catálogo de
+// sky.rs como puntos (vs_stars). Todo depende sólo de la dirección en el
+// mundo, así que gira con la cámara y no se mueve al trasladarla
+
+struct Sky {
+    // Orientación de la cámara de los cuerpos (mundo a cámara), sin
+    // traslación, y su encuadre: como la vista ortográfica, [-1, 1] se estira
+    // a toda la ventana en los dos ejes
+    rotation: mat4x4<f32>,
+    galactic_pole: vec3<f32>,
+    tan_half_fov: f32,
+    galactic_center: vec3<f32>,
+    resolution: vec2<f32>,
+    time: f32,
+    seed: u32,
+    star_count: u32,
+}
+
+struct Star {
+    direction: vec3<f32>,
+    magnitude: f32,
+    color: vec3<f32>,
+    twinkle: f32,
+}
+
+struct Stars {
+    stars: array<Star, 2000>,
+}
+
+@group(0) @binding(4)
+var<uniform> sky: Sky;
+
+@group(0) @binding(5)
+var<uniform> catalog: Stars;
+
+struct SkyOutput {
+    @builtin(position) clip_position: vec4<f32>,
+    // Pantalla en [-1, 1] (fs_sky) o esquina del punto (fs_stars)
+    @location(0) coord: vec2<f32>,
+    @location(1) color: vec3<f32>,
+}
+
+// Detrás de todo lo que se dibuje después
+const SKY_DEPTH = 0.9999;
+
+fn sky_rotation() -> mat3x3<f32> {
+    return mat3x3<f32>(sky.rotation[0].xyz, sky.rotation[1].xyz, sky.rotation[2].xyz);
+}
+
+@vertex
+fn vs_sky(input: VertexInput) -> SkyOutput {
+    var output: SkyOutput;
+    output.clip_position = vec4<f32>(input.position.xy, SKY_DEPTH, 1.0);
+    output.coord = input.position.xy;
+    output.color = vec3<f32>(0.0);
+    return output;
+}
+
+@fragment
+fn fs_sky(input: SkyOutput) -> @location(0) vec4<f32> {
+    set_noise_seed(sky.seed);
+    
+    // Rayo de la cámara por este píxel, llevado al mundo (la inversa de una
+    // rotación es su transpuesta)
+    let ray = normalize(vec3<f32>(input.coord.x * sky.tan_half_fov, input.coord.y * sky.tan_half_fov, -1.0));
+    let dir = transpose(sky_rotation()) * ray;
+    
+    // Banda de la Vía Láctea alrededor del plano galáctico, más ancha y
+    // amarillenta hacia el centro de la galaxia
+    let latitude = dot(dir, sky.galactic_pole);
+    let band = exp(-latitude * latitude / (2.0 * 0.12 * 0.12));
+    let to_center = acos(clamp(dot(dir, sky.galactic_center), -1.0, 1.0));
+    let bulge = exp(-to_center * to_center / (2.0 * 0.5 * 0.5));
+    let star_clouds = value_fbm(dir * 3.0, 5);
+    let grain = value_fbm(dir * 24.0, 3);
+    var glow = band * (0.3 + star_clouds * 0.9) * (0.6 + grain * 0.6) * (0.5 + bulge * 1.5);
+    
+    // Polvo: franjas oscuras pegadas al plano
+    let dust = smoothstep(0.5, 0.65, value_fbm(dir * 6.0 + vec3<f32>(13.0), 4));
+    glow *= 1.0 - 0.85 * dust * exp(-latitude * latitude / (2.0 * 0.05 * 0.05));
+    let galaxy_color = mix(vec3<f32>(0.55, 0.62, 0.85), vec3<f32>(1.0, 0.85, 0.62), bulge);
+    
+    // Nebulosas de emisión, rojizas (hidrógeno) o verdeazuladas (oxígeno), más
+    // frecuentes cerca del plano
+    let nebula = smoothstep(0.2, 0.55, fbm(dir * 2.0 + vec3<f32>(41.0), 5)) * (0.3 + band * 0.7);
+    let nebula_color = mix(vec3<f32>(0.85, 0.22, 0.32), vec3<f32>(0.2, 0.6, 0.7), value_fbm(dir * 1.2 - vec3<f32>(7.0), 2));
+    
+    let background = vec3<f32>(0.004, 0.005, 0.012);
+    return vec4<f32>(background + galaxy_color * glow * 0.1 + nebula_color * nebula * 0.05, 1.0);
+}
+
+// Radio del punto en píxeles y brillo de una estrella según su magnitud; el
+// brillo real cae 2.512 veces por magnitud, aquí se comprime a la mitad
+// (en escala logarítmica) para que las débiles se lleguen a ver
+fn star_radius(magnitude: f32) -> f32 {
+    return clamp(2.6 - magnitude * 0.3, 1.0, 3.5);
+}
+
+fn star_brightness(magnitude: f32) -> f32 {
+    return pow(10.0, -0.2 * magnitude);
+}
+
+@vertex
+fn vs_stars(input: VertexInput, @builtin(instance_index) index: u32) -> SkyOutput {
+    var output: SkyOutput;
+    let star = catalog.stars[index];
+    
+    // Dirección en la cámara, proyectada en perspectiva; las de detrás quedan
+    // fuera del volumen de recorte
+    let view = sky_rotation() * star.direction;
+    if (view.z >= -1e-3 || index >= sky.star_count) {
+        output.clip_position = vec4<f32>(2.0, 2.0, 2.0, 1.0);
+        return output;
+    }
+    let screen = vec2<f32>(view.x, view.y) / (-view.z * sky.tan_half_fov);
+    
+    let radius = star_radius(star.magnitude);
+    let offset = input.position.xy * radius * 2.0 / sky.resolution;
+    output.clip_position = vec4<f32>(screen + offset, SKY_DEPTH, 1.0);
+    output.coord = input.position.xy;
+    
+    // Centelleo suave, cada estrella con su fase
+    let twinkle = 1.0 + 0.15 * sin(sky.time * (2.0 + star.twinkle * 3.0) + star.twinkle * 6.2832);
+    output.color = star.color * star_brightness(star.magnitude) * twinkle;
+    return output;
+}
+
+@fragment
+fn fs_stars(input: SkyOutput) -> @location(0) vec4<f32> {
+    // Perfil gaussiano: un punto con el borde suave
+    let r2 = dot(input.coord, input.coord);
+    if (r2 > 1.0) {
+        discard;
+    }
+    return vec4<f32>(input.color * exp(-r2 * 4.0), 0.0);
 }
@@ -1,14 +1,21 @@
-use nalgebra_glm::{Vec3, Vec4, Mat4};
+use nalgebra_glm::{Vec3, Vec4};
 use crate::vertex::Vertex;
 use crate::Uniforms;
 use crate::fragment::Fragment;
 use crate::color::Color;
+use crate::noise::{unit_float, Noise};
+
+pub fn vertex_shader(vertex: &Vertex, uniforms: &Uniforms, shader_type: u8) -> Vertex {
+    let (surface_position, surface_normal, surface_tangent) = if uniforms.displacement != 0.0 {
+        displace_vertex(vertex, uniforms, &Noise::new(uniforms.seed), shader_type)
+    } else {
+        (vertex.position, vertex.normal, vertex.tangent)
+    };
 
-pub fn vertex_shader(vertex: &Vertex, uniforms: &Uniforms) -> Vertex {
     let position = Vec4::new(
-        vertex.position.x,
-        vertex.position.y,
-        vertex.position.z,
+        surface_position.x,
+        surface_position.y,
+        surface_position.z,
         1.0
     );
 
@@ -24,90 +31,229 @@ pub fn vertex_shader(vertex: &Vertex, uniforms: &Uniforms) -> Vertex {
 
     let screen_position = uniforms.viewport_matrix * ndc_position;
 
-    let model_mat3 = Mat4::identity();
-    let normal_matrix = model_mat3;
+    // Inversa transpuesta de la matriz de modelo (crate::normal_matrix), que el
+    // que dibuja calcula una vez por dibujo y no por vértice
+    let normal_matrix = uniforms.normal_matrix;
 
     let normal_vector = Vec4::new(
-        vertex.normal.x,
-        vertex.normal.y,
-        vertex.normal.z,
+        surface_normal.x,
+        surface_normal.y,
+        surface_normal.z,
         0.0
     );
 
     let transformed_normal = normal_matrix * normal_vector;
 
     Vertex {
-        position: vertex.position,
-        normal: vertex.normal,
+        position: surface_position,
+        normal: surface_normal,
         tex_coords: vertex.tex_coords,
+        tangent: surface_tangent,
         transformed_position: Vec3::new(screen_position.x, screen_position.y, screen_position.z),
         transformed_normal: Vec3::new(transformed_normal.x, transformed_normal.y, transformed_normal.z).normalize(),
     }
 }
 
-pub fn fragment_shader(fragment: &Fragment, uniforms: &Uniforms, shader_type: u8) -> Color {
+// Relieve: se desplaza el vértice y dos vecinos sobre la tangente y la
+// bitangente, y la normal sale del plano que forman
+fn displace_vertex(vertex: &Vertex, uniforms: &Uniforms, noise: &Noise, shader_type: u8) -> (Vec3, Vec3, Vec4) {
+    let displace = |p: Vec3, n: Vec3| p + n * terrain_height(noise, p.normalize(), shader_type) * uniforms.displacement;
+
+    let n = vertex.normal.normalize();
+    let t = vertex.tangent.xyz() - n * n.dot(&vertex.tangent.xyz());
+    let t = if t.norm() > 1e-6 { t.normalize() } else { any_perpendicular(&n) };
+    let b = n.cross(&t);
+    let eps = 0.01;
+
+    let p0 = displace(vertex.position, n);
+    let pt = displace(vertex.position + t * eps, n);
+    let pb = displace(vertex.position + b * eps, n);
+
+    let normal = (pt - p0).cross(&(pb - p0)).normalize();
+    let tangent = (pt - p0).normalize();
+    (p0, normal, Vec4::new(tangent.x, tangent.y, tangent.z, vertex.tangent.w))
+}
+
+fn any_perpendicular(n: &Vec3) -> Vec3 {
+    let axis = if n.y.abs() < 0.99 { Vec3::y() } else { Vec3::x() };
+    axis.cross(n).normalize()
+}
+
+// Altura del terreno en un punto de la esfera unitaria, con las mismas
+// funciones que usan los shaders de color; 0 para cuerpos sin relieve
+fn terrain_height(noise: &Noise, pos: Vec3, shader_type: u8) -> f32 {
     match shader_type {
-        1 => sun_shader(fragment, uniforms),
-        2 => rocky_planet_shader(fragment, uniforms),
-        3 => gas_giant_shader(fragment, uniforms),
-        4 => ringed_planet_shader(fragment, uniforms),
-        5 => planet_with_moon_shader(fragment, uniforms),
-        6 => moon_shader(fragment, uniforms),
-        _ => Color::new(255, 255, 255),
+        2 => rocky_height(noise, pos),
+        6 => moon_height(noise, pos),
+        _ => 0.0,
     }
 }
 
-// Funciones matemáticas rápidas para patrones procedurales
-#[inline(always)]
-fn fast_noise(p: Vec3) -> f32 {
-    ((p.x * 12.9898 + p.y * 78.233 + p.z * 37.719).sin() * 43758.5453).fract()
+// Luz puntual del sol con caída 1/d²: n·l por la irradiancia que llega al
+// punto, igual que sun_diffuse en shader.wgsl
+fn sun_light(fragment: &Fragment, uniforms: &Uniforms) -> f32 {
+    let world = world_position(fragment, uniforms);
+    let to_light = uniforms.light_position - world;
+    fragment.normal.normalize().dot(&to_light.normalize()).max(0.0) * sun_irradiance(world, uniforms)
+}
+
+// Caída 1/d², menos lo que tapen otros cuerpos
+fn sun_irradiance(world: Vec3, uniforms: &Uniforms) -> f32 {
+    let to_light = uniforms.light_position - world;
+    uniforms.light_intensity / to_light.dot(&to_light).max(1e-4) * eclipse(world, uniforms)
+}
+
+// Parte visible del disco del sol desde el punto (0 en la umbra), igual que
+// eclipse en shader.wgsl
+fn eclipse(world: Vec3, uniforms: &Uniforms) -> f32 {
+    let to_sun = uniforms.light_position - world;
+    let sun_dist = to_sun.norm();
+    let sun_angle = (uniforms.sun_radius / sun_dist.max(1e-4)).clamp(0.0, 1.0).asin();
+
+    let mut visible = 1.0;
+    for &[x, y, z, radius] in &uniforms.occluders {
+        let to_body = Vec3::new(x, y, z) - world;
+        let body_dist = to_body.norm();
+        if radius <= 0.0 || body_dist <= radius || body_dist >= sun_dist || to_body.dot(&to_sun) <= 0.0 {
+            continue;
+        }
+        let body_angle = (radius / body_dist).asin();
+        let separation = (to_body.dot(&to_sun) / (body_dist * sun_dist)).clamp(-1.0, 1.0).acos();
+        visible *= 1.0 - disc_overlap(sun_angle, body_angle, separation);
+    }
+    visible
+}
+
+// Fracción del disco de radio r1 tapada por uno de radio r2 a distancia d
+fn disc_overlap(r1: f32, r2: f32, d: f32) -> f32 {
+    if d >= r1 + r2 {
+        return 0.0;
+    }
+    if r1 < 1e-5 {
+        return 1.0;
+    }
+    if d <= (r1 - r2).abs() {
+        return (r2 * r2 / (r1 * r1)).min(1.0);
+    }
+    let a = ((d * d + r1 * r1 - r2 * r2) / (2.0 * d * r1)).clamp(-1.0, 1.0).acos();
+    let b = ((d * d + r2 * r2 - r1 * r1) / (2.0 * d * r2)).clamp(-1.0, 1.0).acos();
+    let area = r1 * r1 * (a - 0.5 * (2.0 * a).sin()) + r2 * r2 * (b - 0.5 * (2.0 * b).sin());
+    (area / (std::f32::consts::PI * r1 * r1)).clamp(0.0, 1.0)
+}
+
+// Luz reflejada en el lado de noche (sin ella queda negro del todo)
+const AMBIENT: f32 = 0.04;
+
+// 1 de noche, 0 de día, con una transición suave en el terminador
+fn sun_darkness(fragment: &Fragment, uniforms: &Uniforms) -> f32 {
+    let to_light = (uniforms.light_position - world_position(fragment, uniforms)).normalize();
+    let t = ((fragment.normal.normalize().dot(&to_light) + 0.1) / 0.3).clamp(0.0, 1.0);
+    1.0 - t * t * (3.0 - 2.0 * t)
+}
+
+fn world_position(fragment: &Fragment, uniforms: &Uniforms) -> Vec3 {
+    let p = fragment.vertex_position;
+    (uniforms.model_matrix * Vec4::new(p.x, p.y, p.z, 1.0)).xyz()
+}
+
+// Reflejo GGX del sol en una superficie dieléctrica (Fresnel de Schlick con
+// F0 = 0.02), igual que sun_specular en shader.wgsl: con rugosidad 1 o más la
+// superficie es mate y no hay reflejo
+fn sun_specular(fragment: &Fragment, uniforms: &Uniforms, roughness: f32) -> f32 {
+    if roughness >= 1.0 {
+        return 0.0;
+    }
+    let world = world_position(fragment, uniforms);
+    let to_light = uniforms.light_position - world;
+    let irradiance = sun_irradiance(world, uniforms);
+
+    let normal = fragment.normal.normalize();
+    let light_dir = to_light.normalize();
+    let view_dir = uniforms.view_dir;
+    let half_dir = (light_dir + view_dir).normalize();
+    let roughness = roughness.clamp(0.04, 1.0);
+
+    let n_dot_l = normal.dot(&light_dir).max(0.0);
+    let n_dot_v = normal.dot(&view_dir).max(0.001);
+    let n_dot_h = normal.dot(&half_dir).max(0.0);
+    let v_dot_h = view_dir.dot(&half_dir).max(0.0);
+
+    let a2 = roughness.powi(4);
+    let d = n_dot_h * n_dot_h * (a2 - 1.0) + 1.0;
+    let distribution = a2 / (std::f32::consts::PI * d * d);
+    let k = (roughness + 1.0) * (roughness + 1.0) / 8.0;
+    let geometry = (n_dot_v / (n_dot_v * (1.0 - k) + k)) * (n_dot_l / (n_dot_l * (1.0 - k) + k));
+    let fresnel = 0.02 + 0.98 * (1.0 - v_dot_h).powf(5.0);
+
+    fresnel * distribution * geometry / (4.0 * n_dot_v * n_dot_l.max(0.001)) * n_dot_l * irradiance
+}
+
+// Índices de las rampas, en el orden de ramp.rs (igual que en shader.wgsl)
+const SUN_TEMPERATURE_RAMP: usize = 0;
+const ROCKY_OCEAN_RAMP: usize = 0;
+const ROCKY_VEGETATION_RAMP: usize = 1;
+const GAS_BANDS_RAMP: usize = 0;
+const RINGED_RINGS_RAMP: usize = 0;
+const VOLCANIC_LAVA_RAMP: usize = 0;
+const VOLCANIC_ROCK_RAMP: usize = 1;
+const MOON_HIGHLANDS_RAMP: usize = 0;
+
+// Color de una rampa del cuerpo en t; negro si el cuerpo no la tiene
+fn ramp_color(uniforms: &Uniforms, index: usize, t: f32) -> Color {
+    match uniforms.ramps.get(index) {
+        Some(ramp) => {
+            let [r, g, b] = ramp.sample(t);
+            Color::from_float(r, g, b)
+        }
+        None => Color::new(0, 0, 0),
+    }
 }
 
+pub fn fragment_shader(fragment: &Fragment, uniforms: &Uniforms, shader_type: u8) -> Color {
+    // Todos los patrones del cuerpo salen de su semilla
+    let noise = Noise::new(uniforms.seed);
+    match shader_type {
+        1 => sun_shader(fragment, uniforms, &noise),
+        2 => rocky_planet_shader(fragment, uniforms, &noise),
+        3 => gas_giant_shader(fragment, uniforms, &noise),
+        4 => ringed_planet_shader(fragment, uniforms, &noise),
+        5 => planet_with_moon_shader(fragment, uniforms, &noise),
+        6 => moon_shader(fragment, uniforms, &noise),
+        _ => Color::new(255, 255, 255),
+    }
+}
+
+// Patrones procedurales rápidos (sin uso por ahora)
+#[allow(dead_code)]
 #[inline(always)]
-fn pattern1(p: Vec3, time: f32) -> f32 {
+fn pattern1(noise: &Noise, p: Vec3, time: f32) -> f32 {
+    let p = p + noise.seed_offset();
     let a = (p.x * 3.0 + time).sin();
     let b = (p.y * 3.0 - time * 0.5).cos();
     let c = (p.z * 3.0 + time * 0.3).sin();
     (a + b + c) * 0.333
 }
 
+#[allow(dead_code)]
 #[inline(always)]
-fn pattern2(p: Vec3, time: f32) -> f32 {
+fn pattern2(noise: &Noise, p: Vec3, time: f32) -> f32 {
+    let p = p + noise.seed_offset();
     let freq = 8.0;
     ((p.x * freq).sin() * (p.y * freq).cos() + (p.z * freq + time).sin()) * 0.5
 }
 
-#[inline(always)]
-fn voronoi_simple(p: Vec3) -> f32 {
-    let pi = Vec3::new(p.x.floor(), p.y.floor(), p.z.floor());
-    let pf = Vec3::new(p.x.fract(), p.y.fract(), p.z.fract());
-    
-    let mut min_dist: f32 = 2.0;
-    for i in -1..=1 {
-        for j in -1..=1 {
-            let neighbor = Vec3::new(i as f32, j as f32, 0.0);
-            let point = neighbor + Vec3::new(
-                fast_noise(pi + neighbor),
-                fast_noise(pi + neighbor + Vec3::new(0.1, 0.1, 0.1)),
-                0.0
-            );
-            let diff = point - pf;
-            let dist = diff.x * diff.x + diff.y * diff.y;
-            min_dist = min_dist.min(dist);
-        }
-    }
-    min_dist.sqrt()
-}
-
 // ===== SHADER 1: SOL CON PLASMA ANIMADO =====
-fn sun_shader(fragment: &Fragment, uniforms: &Uniforms) -> Color {
+fn sun_shader(fragment: &Fragment, uniforms: &Uniforms, noise: &Noise) -> Color {
     let pos = fragment.vertex_position * 3.0;
+    // Las ondas se muestrean desplazadas por la semilla; los vórtices no,
+    // porque dependen del ángulo alrededor del eje
+    let q = pos + noise.seed_offset();
     let time = uniforms.time as f32 * 0.02;
     
     // Plasma con múltiples ondas
-    let wave1 = ((pos.x * 4.0 + time).sin() + (pos.y * 3.0 - time * 0.7).cos()) * 0.5;
-    let wave2 = ((pos.y * 5.0 + time * 1.3).sin() + (pos.z * 4.0 + time).sin()) * 0.5;
-    let wave3 = ((pos.x * 2.0 - time * 0.5).cos() * (pos.y * 2.0 + time * 0.8).sin()) * 0.5;
+    let wave1 = ((q.x * 4.0 + time).sin() + (q.y * 3.0 - time * 0.7).cos()) * 0.5;
+    let wave2 = ((q.y * 5.0 + time * 1.3).sin() + (q.z * 4.0 + time).sin()) * 0.5;
+    let wave3 = ((q.x * 2.0 - time * 0.5).cos() * (q.y * 2.0 + time * 0.8).sin()) * 0.5;
     
     let plasma = (wave1 + wave2 + wave3) * 0.5 + 0.5;
     
@@ -117,79 +263,87 @@ fn sun_shader(fragment: &Fragment, uniforms: &Uniforms) -> Color {
     let spiral = ((angle * 8.0 + radius * 6.0 - time * 3.0).sin() + 1.0) * 0.5;
     
     // Manchas solares (zonas oscuras)
-    let spot_pattern = ((pos.x * 8.0).sin() * (pos.y * 8.0).cos() + (pos.z * 8.0 + time * 0.1).sin());
+    let spot_pattern = (q.x * 8.0).sin() * (q.y * 8.0).cos() + (q.z * 8.0 + time * 0.1).sin();
     let spots = if spot_pattern > 0.8 { 0.5 } else { 1.0 };
     
-    // Pulsación de corona
-    let dist = (pos.x * pos.x + pos.y * pos.y + pos.z * pos.z).sqrt();
-    let corona = (1.0 - dist * 0.3).max(0.0).powf(3.0);
-    let pulse = (time * 4.0).sin() * 0.2 + 0.8;
-    
     // Gradiente de temperatura
     let temp = plasma * spiral;
-    let base_color = if temp > 0.7 {
-        Color::new(255, 255, 220) // Blanco caliente
-    } else if temp > 0.5 {
-        Color::new(255, 240, 150) // Amarillo brillante
-    } else if temp > 0.3 {
-        Color::new(255, 180, 80) // Naranja
-    } else {
-        Color::new(255, 120, 40) // Rojo-naranja
-    };
+    let base_color = ramp_color(uniforms, SUN_TEMPERATURE_RAMP, temp);
+    
+    // Oscurecimiento del borde, más fuerte en el azul (limb_darkening en
+    // shader.wgsl); la corona sólo la dibuja el renderizador de wgpu
+    let mu = fragment.normal.normalize().dot(&uniforms.view_dir).clamp(0.0, 1.0);
+    let limb = |u: f32| (1.0 - 0.6 * u * (1.0 - mu)).max(0.0);
     
     let with_spots = base_color.mul(spots);
-    with_spots.mul(1.0 + corona * pulse * 0.8)
+    Color::from_float(
+        with_spots.r as f32 / 255.0 * limb(0.85),
+        with_spots.g as f32 / 255.0 * limb(1.0),
+        with_spots.b as f32 / 255.0 * limb(1.2),
+    )
 }
 
 // ===== SHADER 2: PLANETA TIERRA CON CONTINENTES Y NUBES =====
-fn rocky_planet_shader(fragment: &Fragment, uniforms: &Uniforms) -> Color {
+fn rocky_terrain(noise: &Noise, pos: Vec3) -> f32 {
+    // Generar continentes con patrón Voronoi; cada placa sube o baja un poco según su id
+    let plates = noise.worley(pos * 0.8);
+    let q = pos + noise.seed_offset();
+    let continents = plates.f1 + (unit_float(plates.id) - 0.5) * 0.1;
+    let mountains = ((q.x * 10.0).sin() * (q.y * 10.0).cos() + (q.z * 10.0).sin() + 1.0) * 0.5;
+
+    continents * 0.7 + mountains * 0.3
+}
+
+// Los océanos quedan planos al nivel del mar
+fn rocky_height(noise: &Noise, pos: Vec3) -> f32 {
+    (rocky_terrain(noise, pos * 5.0) - 0.35).max(0.0)
+}
+
+fn rocky_planet_shader(fragment: &Fragment, uniforms: &Uniforms, noise: &Noise) -> Color {
     let pos = fragment.vertex_position * 5.0;
+    let q = pos + noise.seed_offset();
     let time = uniforms.time as f32 * 0.005;
     
-    // Generar continentes con patrón Voronoi
-    let continents = voronoi_simple(pos * 0.8);
-    let mountains = ((pos.x * 10.0).sin() * (pos.y * 10.0).cos() + (pos.z * 10.0).sin() + 1.0) * 0.5;
-    
-    let terrain_height = continents * 0.7 + mountains * 0.3;
+    let terrain_height = rocky_terrain(noise, pos);
     
     let is_ocean = terrain_height < 0.35;
-    let is_land = terrain_height >= 0.35 && terrain_height < 0.55;
-    let is_mountain = terrain_height >= 0.55 && terrain_height < 0.65;
+    let is_land = (0.35..0.55).contains(&terrain_height);
+    let is_mountain = (0.55..0.65).contains(&terrain_height);
     let is_snow = terrain_height >= 0.65;
     
+    // El agua es lisa y refleja el sol; la tierra es rugosa
+    let mut roughness = if is_ocean { 0.15 } else { 0.8 };
+
+    // Luces de ciudades en la tierra baja, sólo visibles de noche
+    let population = ((q.x * 7.0 + 3.1).sin() * (q.z * 7.0).cos() + (q.y * 5.0).sin() + 1.5) * 0.33;
+    let lights = ((q.x * 90.0).sin() * (q.y * 90.0).cos() * (q.z * 90.0).sin()).max(0.0);
+    let mut city_lights = if is_land && population > 0.6 { lights } else { 0.0 };
+
     // Colores base del terreno
     let mut color = if is_ocean {
         let depth = (0.35 - terrain_height) * 5.0;
-        if depth > 0.6 {
-            Color::new(10, 40, 100) // Océano profundo
-        } else {
-            Color::new(30, 80, 160) // Océano normal
-        }
+        ramp_color(uniforms, ROCKY_OCEAN_RAMP, depth)
     } else if is_snow {
         Color::new(250, 250, 255) // Nieve
     } else if is_mountain {
         Color::new(130, 110, 90) // Montañas rocosas
     } else {
         // Variación de vegetación
-        let veg = ((pos.x * 15.0).sin() + (pos.y * 15.0).cos() + 1.0) * 0.5;
-        if veg > 0.6 {
-            Color::new(50, 140, 50) // Bosques densos
-        } else if veg > 0.4 {
-            Color::new(100, 160, 70) // Pastizales
-        } else {
-            Color::new(210, 190, 140) // Desiertos
-        }
+        let veg = ((q.x * 15.0).sin() + (q.y * 15.0).cos() + 1.0) * 0.5;
+        ramp_color(uniforms, ROCKY_VEGETATION_RAMP, veg)
     };
     
     // Sistema de nubes dinámicas
-    let cloud1 = ((pos.x * 3.0 + time * 20.0).sin() + (pos.y * 3.0).cos() + (pos.z * 3.0 + time * 15.0).sin() + 1.5) * 0.33;
-    let cloud2 = ((pos.x * 6.0 - time * 15.0).cos() + (pos.y * 6.0).sin() + 1.0) * 0.5;
+    let cloud1 = ((q.x * 3.0 + time * 20.0).sin() + (q.y * 3.0).cos() + (q.z * 3.0 + time * 15.0).sin() + 1.5) * 0.33;
+    let cloud2 = ((q.x * 6.0 - time * 15.0).cos() + (q.y * 6.0).sin() + 1.0) * 0.5;
     let clouds = (cloud1 * 0.7 + cloud2 * 0.3).clamp(0.0, 1.0);
     
     if clouds > 0.6 {
         let density = ((clouds - 0.6) / 0.4).min(1.0);
         let cloud_color = Color::new(255, 255, 255);
         color = color.lerp(&cloud_color, density * 0.85);
+        roughness += (1.0 - roughness) * density;
+        city_lights *= 1.0 - density * 0.85;
     }
     
     // Atmósfera azul
@@ -201,45 +355,36 @@ fn rocky_planet_shader(fragment: &Fragment, uniforms: &Uniforms) -> Color {
     }
     
     // Iluminación
-    let light_dir = Vec3::new(0.8, 0.5, 1.0).normalize();
-    let normal = fragment.normal.normalize();
-    let diffuse = normal.dot(&light_dir).max(0.15);
+    let diffuse = AMBIENT + sun_light(fragment, uniforms);
+    let specular = sun_specular(fragment, uniforms, roughness);
+    let night = city_lights * sun_darkness(fragment, uniforms);
     
     color.mul(diffuse)
+        .add(&Color::from_float(specular, specular, specular))
+        .add(&Color::from_float(night, night * 0.78, night * 0.42))
 }
 
 // ===== SHADER 3: JÚPITER CON BANDAS Y GRAN MANCHA ROJA =====
-fn gas_giant_shader(fragment: &Fragment, uniforms: &Uniforms) -> Color {
+fn gas_giant_shader(fragment: &Fragment, uniforms: &Uniforms, noise: &Noise) -> Color {
     let pos = fragment.vertex_position * 3.5;
+    // Las bandas siguen la latitud; la turbulencia cambia con la semilla
+    let q = pos + noise.seed_offset();
     let time = uniforms.time as f32 * 0.01;
     
     // Bandas horizontales con turbulencia
     let base_bands = pos.y * 18.0;
-    let turb1 = ((pos.x * 5.0 + time * 2.0).sin() + (pos.z * 5.0 + time).cos()) * 0.8;
-    let turb2 = ((pos.x * 10.0 - time).cos() + (pos.z * 10.0).sin()) * 0.3;
+    let turb1 = ((q.x * 5.0 + time * 2.0).sin() + (q.z * 5.0 + time).cos()) * 0.8;
+    let turb2 = ((q.x * 10.0 - time).cos() + (q.z * 10.0).sin()) * 0.3;
     
     let band_pos = base_bands + turb1 + turb2;
     let bands = (band_pos.sin() + 1.0) * 0.5;
     
     // Más turbulencia atmosférica
-    let atmosphere_chaos = ((pos.x * 8.0 + time * 1.5).sin() * (pos.y * 6.0).cos() + (pos.z * 7.0 - time * 0.8).sin() + 1.5) * 0.33;
+    let atmosphere_chaos = ((q.x * 8.0 + time * 1.5).sin() * (q.y * 6.0).cos() + (q.z * 7.0 - time * 0.8).sin() + 1.5) * 0.33;
     let band_value = (bands * 0.6 + atmosphere_chaos * 0.4).clamp(0.0, 1.0);
     
     // Paleta joviana
-    let color1 = Color::new(250, 230, 190); // Beige muy claro
-    let color2 = Color::new(170, 120, 80);  // Marrón
-    let color3 = Color::new(210, 180, 140); // Beige medio
-    let color4 = Color::new(255, 245, 220); // Blanco cremoso
-    
-    let mut final_color = if band_value < 0.25 {
-        color1.lerp(&color2, band_value * 4.0)
-    } else if band_value < 0.5 {
-        color2.lerp(&color3, (band_value - 0.25) * 4.0)
-    } else if band_value < 0.75 {
-        color3.lerp(&color4, (band_value - 0.5) * 4.0)
-    } else {
-        color4.lerp(&color1, (band_value - 0.75) * 4.0)
-    };
+    let mut final_color = ramp_color(uniforms, GAS_BANDS_RAMP, band_value);
     
     // GRAN MANCHA ROJA visible y animada
     let spot_center = Vec3::new(0.6, -0.3, 0.0);
@@ -266,20 +411,19 @@ fn gas_giant_shader(fragment: &Fragment, uniforms: &Uniforms) -> Color {
     }
     
     // Iluminación
-    let light_dir = Vec3::new(1.0, 0.3, 0.8).normalize();
-    let normal = fragment.normal.normalize();
-    let diffuse = normal.dot(&light_dir).max(0.2);
+    let diffuse = AMBIENT + sun_light(fragment, uniforms);
     
     final_color.mul(diffuse)
 }
 
 // ===== SHADER 4: SATURNO CON ANILLOS ESPECTACULARES Y VISIBLES =====
-fn ringed_planet_shader(fragment: &Fragment, uniforms: &Uniforms) -> Color {
+fn ringed_planet_shader(fragment: &Fragment, uniforms: &Uniforms, noise: &Noise) -> Color {
     let pos = fragment.vertex_position * 3.0;
+    let q = pos + noise.seed_offset();
     let time = uniforms.time as f32 * 0.006;
     
     // Planeta con bandas suaves
-    let bands = ((pos.y * 20.0 + ((pos.x * 3.0).sin() + (pos.z * 3.0).cos()) * 0.5).sin() + 1.0) * 0.5;
+    let bands = ((pos.y * 20.0 + ((q.x * 3.0).sin() + (q.z * 3.0).cos()) * 0.5).sin() + 1.0) * 0.5;
     let color1 = Color::new(255, 240, 210);
     let color2 = Color::new(240, 220, 180);
     let mut planet_color = color1.lerp(&color2, bands);
@@ -307,13 +451,7 @@ fn ringed_planet_shader(fragment: &Fragment, uniforms: &Uniforms) -> Color {
             planet_color = planet_color.mul(0.4);
         } else {
             // Anillos visibles con colores variados
-            let ring_color = if ring_bands > 0.7 {
-                Color::new(245, 225, 190) // Anillos claros
-            } else if ring_bands > 0.4 {
-                Color::new(210, 185, 145) // Anillos medios
-            } else {
-                Color::new(180, 160, 125) // Anillos oscuros
-            };
+            let ring_color = ramp_color(uniforms, RINGED_RINGS_RAMP, ring_bands);
             
             // Transparencia basada en distancia al plano
             let ring_alpha = (1.0 - (y_abs / 0.18).powf(1.2)) * 0.95;
@@ -332,102 +470,93 @@ fn ringed_planet_shader(fragment: &Fragment, uniforms: &Uniforms) -> Color {
     }
     
     // Iluminación
-    let light_dir = Vec3::new(1.0, 0.6, 0.8).normalize();
-    let normal = fragment.normal.normalize();
-    let diffuse = normal.dot(&light_dir).max(0.25);
+    let diffuse = AMBIENT + sun_light(fragment, uniforms);
     
     planet_color.mul(diffuse)
 }
 
 // ===== SHADER 5: PLANETA VOLCÁNICO CON LAVA BRILLANTE =====
-fn planet_with_moon_shader(fragment: &Fragment, uniforms: &Uniforms) -> Color {
+fn planet_with_moon_shader(fragment: &Fragment, uniforms: &Uniforms, noise: &Noise) -> Color {
     let pos = fragment.vertex_position * 4.0;
+    let q = pos + noise.seed_offset();
     let time = uniforms.time as f32 * 0.015;
     
-    // Superficie con patrón Voronoi para grietas
-    let cracks = voronoi_simple(pos * 1.5);
-    let fine_cracks = ((pos.x * 20.0 + time).sin() * (pos.y * 20.0).cos() + (pos.z * 20.0 - time).sin() + 1.5) * 0.33;
+    // Red de grietas en los bordes de las celdas de Worley
+    let cracks = noise.worley(pos * 1.5).edge;
+    let fine_cracks = ((q.x * 20.0 + time).sin() * (q.y * 20.0).cos() + (q.z * 20.0 - time).sin() + 1.5) * 0.33;
     
-    let is_lava = cracks < 0.4 || fine_cracks > 0.8;
+    let is_lava = cracks < 0.12 || fine_cracks > 0.8;
     
-    let mut color = if is_lava {
+    // La roca refleja la luz del sol; la lava brilla por sí misma, también de noche
+    let (color, mut emission) = if is_lava {
         // Lava con pulsación de temperatura
-        let heat_pattern = ((pos.x * 6.0 + time * 3.0).sin() + (pos.y * 6.0).cos() + (pos.z * 6.0 + time * 2.0).sin() + 1.5) * 0.33;
+        let heat_pattern = ((q.x * 6.0 + time * 3.0).sin() + (q.y * 6.0).cos() + (q.z * 6.0 + time * 2.0).sin() + 1.5) * 0.33;
         let pulse = (time * 5.0).sin() * 0.25 + 0.75;
         
-        if heat_pattern > 0.75 {
-            Color::new(255, 255, 220).mul(pulse) // Lava blanca (ultra caliente)
-        } else if heat_pattern > 0.55 {
-            Color::new(255, 230, 120).mul(pulse) // Amarilla
-        } else if heat_pattern > 0.35 {
-            Color::new(255, 150, 50).mul(pulse) // Naranja
-        } else {
-            Color::new(220, 70, 30).mul(pulse) // Roja
-        }
+        (ramp_color(uniforms, VOLCANIC_ROCK_RAMP, 0.0), ramp_color(uniforms, VOLCANIC_LAVA_RAMP, heat_pattern).mul(pulse))
     } else {
         // Roca solidificada oscura
-        let rock_var = ((pos.x * 25.0).sin() + (pos.y * 25.0).cos() + 1.0) * 0.5;
-        if rock_var > 0.6 {
-            Color::new(70, 60, 55) // Gris oscuro
-        } else {
-            Color::new(35, 30, 25) // Casi negro
-        }
+        let rock_var = ((q.x * 25.0).sin() + (q.y * 25.0).cos() + 1.0) * 0.5;
+        (ramp_color(uniforms, VOLCANIC_ROCK_RAMP, rock_var), Color::new(0, 0, 0))
     };
     
     // Grietas ultra brillantes
     if fine_cracks > 0.88 {
         let glow_intensity = (fine_cracks - 0.88) / 0.12;
         let crack_glow = Color::new(255, 220, 100);
-        color = color.lerp(&crack_glow, glow_intensity);
+        emission = emission.lerp(&crack_glow, glow_intensity);
     }
     
     // Resplandor ambiental
-    let ambient_glow = ((pos.x * 3.0 - time * 0.8).sin() + (pos.z * 3.0 + time * 0.5).cos() + 1.0) * 0.15;
+    let ambient_glow = ((q.x * 3.0 - time * 0.8).sin() + (q.z * 3.0 + time * 0.5).cos() + 1.0) * 0.15;
     let glow_color = Color::new((ambient_glow * 255.0) as u8, (ambient_glow * 120.0) as u8, 0);
-    color = color.add(&glow_color);
+    emission = emission.add(&glow_color);
     
-    // Iluminación con auto-emisión
-    let light_dir = Vec3::new(0.8, 0.8, 1.0).normalize();
-    let normal = fragment.normal.normalize();
-    let diffuse = normal.dot(&light_dir).max(0.35);
+    // Iluminación más la luz propia
+    let diffuse = AMBIENT + sun_light(fragment, uniforms);
     
-    color.mul(diffuse * 0.5 + 0.5)
+    color.mul(diffuse).add(&emission)
 }
 
 // ===== SHADER 6: LUNA CON CRÁTERES =====
-fn moon_shader(fragment: &Fragment, _uniforms: &Uniforms) -> Color {
+// Cráteres hundidos y tierras altas elevadas
+fn moon_height(noise: &Noise, pos: Vec3) -> f32 {
+    let pos = pos * 5.0;
+    let q = pos + noise.seed_offset();
+    let crater_depth = (0.25 - noise.worley(pos * 1.2).f1).max(0.0) * 2.0;
+    let highland_pattern = ((q.x * 4.0).sin() + (q.y * 4.0).cos() + (q.z * 4.0).sin() + 1.5) * 0.33;
+    highland_pattern * 0.3 - crater_depth
+}
+
+fn moon_shader(fragment: &Fragment, uniforms: &Uniforms, noise: &Noise) -> Color {
     let pos = fragment.vertex_position * 5.0;
+    let q = pos + noise.seed_offset();
     
     // Cráteres con Voronoi
-    let crater_pattern = voronoi_simple(pos * 1.2);
+    let crater_pattern = noise.worley(pos * 1.2).f1;
     let is_crater = crater_pattern < 0.25;
     
     // Mares lunares (zonas oscuras)
-    let mare_pattern = ((pos.x * 2.0).sin() * (pos.y * 2.0).cos() + (pos.z * 2.0).sin() + 1.0) * 0.5;
+    let mare_pattern = ((q.x * 2.0).sin() * (q.y * 2.0).cos() + (q.z * 2.0).sin() + 1.0) * 0.5;
     let is_mare = mare_pattern < 0.3;
     
     // Tierras altas
-    let highland_pattern = ((pos.x * 4.0).sin() + (pos.y * 4.0).cos() + (pos.z * 4.0).sin() + 1.5) * 0.33;
-    let is_highland = highland_pattern > 0.7;
+    let highland_pattern = ((q.x * 4.0).sin() + (q.y * 4.0).cos() + (q.z * 4.0).sin() + 1.5) * 0.33;
     
     let base_color = if is_crater {
         Color::new(60, 60, 60) // Cráteres oscuros
     } else if is_mare {
         Color::new(80, 80, 80) // Mares lunares
-    } else if is_highland {
-        Color::new(190, 190, 190) // Tierras altas brillantes
     } else {
-        Color::new(140, 140, 140) // Gris base
+        ramp_color(uniforms, MOON_HIGHLANDS_RAMP, highland_pattern)
     };
     
     // Detalle fino de superficie
-    let fine_detail = ((pos.x * 30.0).sin() * (pos.y * 30.0).cos() + (pos.z * 30.0).sin() + 1.0) * 0.5;
+    let fine_detail = ((q.x * 30.0).sin() * (q.y * 30.0).cos() + (q.z * 30.0).sin() + 1.0) * 0.5;
     let final_color = base_color.mul(0.90 + fine_detail * 0.20);
     
     // Iluminación lunar con sombras duras
-    let light_dir = Vec3::new(1.0, 0.3, 0.8).normalize();
-    let normal = fragment.normal.normalize();
-    let diffuse = normal.dot(&light_dir).max(0.10);
+    let diffuse = AMBIENT + sun_light(fragment, uniforms);
     
     final_color.mul(diffuse)
 }
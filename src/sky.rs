@@ -0,0 +1,183 @@
+// Cielo de fondo: un catálogo de estrellas generado con una semilla (dirección,
+// magnitud y color de cada una) y el plano de la Vía Láctea. vs_stars y fs_sky
+// en shader.wgsl los dibujan antes que los cuerpos
+
+use nalgebra_glm::Vec3;
+
+// 2000 estrellas de 32 bytes caben en los 64 KiB de un uniform
+pub const MAX_STARS: usize = 2000;
+// Las más débiles que se ven a simple vista
+pub const FAINTEST_MAGNITUDE: f32 = 6.5;
+pub const BRIGHTEST_MAGNITUDE: f32 = -1.5;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Sky {
+    pub seed: u32,
+    pub stars: u32,
+}
+
+impl Default for Sky {
+    fn default() -> Self {
+        Sky { seed: 1, stars: MAX_STARS as u32 }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Star {
+    // Unitaria, en el mundo: las estrellas están infinitamente lejos
+    pub direction: Vec3,
+    pub magnitude: f32,
+    // RGB con el canal más brillante en 1
+    pub color: [f32; 3],
+    // Fase del centelleo en [0, 1)
+    pub twinkle: f32,
+}
+
+pub struct StarCatalog {
+    pub stars: Vec<Star>,
+    // Normal del plano galáctico y dirección del centro de la galaxia
+    pub galactic_pole: Vec3,
+    pub galactic_center: Vec3,
+}
+
+// Temperaturas de las clases espectrales (O, B, A, F, G, K, M) y cuántas
+// estrellas de cada una hay entre las visibles
+const SPECTRAL_CLASSES: [(f32, f32); 7] = [
+    (30000.0, 0.02),
+    (15000.0, 0.10),
+    (9000.0, 0.20),
+    (7000.0, 0.18),
+    (5800.0, 0.17),
+    (4500.0, 0.20),
+    (3300.0, 0.13),
+];
+
+// Fracción de estrellas concentradas cerca del plano galáctico
+const DISK_FRACTION: f32 = 0.4;
+// Espesor del disco (desviación de la latitud, en radianes)
+const DISK_THICKNESS: f32 = 0.15;
+
+impl StarCatalog {
+    pub fn generate(sky: &Sky) -> Self {
+        let mut rng = Rng::new(sky.seed);
+
+        let galactic_pole = rng.direction();
+        let axis = if galactic_pole.y.abs() < 0.99 { Vec3::y() } else { Vec3::x() };
+        let galactic_center = axis.cross(&galactic_pole).normalize();
+
+        let stars = (0..(sky.stars as usize).min(MAX_STARS))
+            .map(|_| {
+                let mut direction = rng.direction();
+                if rng.next_f32() < DISK_FRACTION {
+                    // Se lleva la estrella hacia el plano, con latitud gaussiana
+                    let in_plane = direction - galactic_pole * direction.dot(&galactic_pole);
+                    let latitude = rng.gaussian() * DISK_THICKNESS;
+                    if in_plane.norm() > 1e-4 {
+                        direction = in_plane.normalize() * latitude.cos() + galactic_pole * latitude.sin();
+                    }
+                }
+
+                // Cada magnitud más débil hay unas 3 veces más estrellas
+                let magnitude = (FAINTEST_MAGNITUDE + 2.0 * rng.next_f32().max(1e-6).log10()).max(BRIGHTEST_MAGNITUDE);
+
+                let mut pick = rng.next_f32();
+                let mut temperature = SPECTRAL_CLASSES[SPECTRAL_CLASSES.len() - 1].0;
+                for &(class_temperature, weight) in &SPECTRAL_CLASSES {
+                    if pick < weight {
+                        temperature = class_temperature;
+                        break;
+                    }
+                    pick -= weight;
+                }
+                temperature *= 0.9 + rng.next_f32() * 0.2;
+
+                Star { direction, magnitude, color: blackbody_color(temperature), twinkle: rng.next_f32() }
+            })
+            .collect();
+
+        StarCatalog { stars, galactic_pole, galactic_center }
+    }
+
+    pub fn to_gpu(&self) -> Vec<GpuStar> {
+        let mut stars = vec![bytemuck::Zeroable::zeroed(); MAX_STARS];
+        for (slot, star) in stars.iter_mut().zip(&self.stars) {
+            *slot = GpuStar {
+                direction: star.direction.into(),
+                magnitude: star.magnitude,
+                color: star.color,
+                twinkle: star.twinkle,
+            };
+        }
+        stars
+    }
+}
+
+// Color aproximado de un cuerpo negro a esa temperatura (Kelvin), ajuste de
+// Tanner Helland, normalizado al canal más brillante
+pub fn blackbody_color(temperature: f32) -> [f32; 3] {
+    let t = temperature / 100.0;
+    let r = if t <= 66.0 { 255.0 } else { 329.698_73 * (t - 60.0).powf(-0.133_204_76) };
+    let g = if t <= 66.0 {
+        99.470_8 * t.ln() - 161.119_57
+    } else {
+        288.122_16 * (t - 60.0).powf(-0.075_514_85)
+    };
+    let b = if t >= 66.0 {
+        255.0
+    } else if t <= 19.0 {
+        0.0
+    } else {
+        138.517_73 * (t - 10.0).ln() - 305.044_8
+    };
+
+    let color = [r, g, b].map(|c: f32| c.clamp(0.0, 255.0) / 255.0);
+    let max = color[0].max(color[1]).max(color[2]).max(1e-6);
+    color.map(|c| c / max)
+}
+
+// SplitMix64: el mismo catálogo para la misma semilla en cualquier máquina
+struct Rng(u64);
+
+impl Rng {
+    fn new(seed: u32) -> Self {
+        Rng(seed as u64 ^ 0x5eed_5eed_5eed_5eed)
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    // En [0, 1)
+    fn next_f32(&mut self) -> f32 {
+        (self.next_u64() >> 40) as f32 / 16777216.0
+    }
+
+    // Box-Muller
+    fn gaussian(&mut self) -> f32 {
+        let u = self.next_f32().max(1e-7);
+        let v = self.next_f32();
+        (-2.0 * u.ln()).sqrt() * (std::f32::consts::TAU * v).cos()
+    }
+
+    // Uniforme sobre la esfera
+    fn direction(&mut self) -> Vec3 {
+        let z = self.next_f32() * 2.0 - 1.0;
+        let angle = self.next_f32() * std::f32::consts::TAU;
+        let r = (1.0 - z * z).max(0.0).sqrt();
+        Vec3::new(r * angle.cos(), r * angle.sin(), z)
+    }
+}
+
+// Una estrella en el uniform de estrellas (binding 5 de la capa del cielo)
+#[repr(C)]
+#[derive(Debug, Clone, Copy, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct GpuStar {
+    direction: [f32; 3],
+    magnitude: f32,
+    color: [f32; 3],
+    twinkle: f32,
+}
@@ -0,0 +1,239 @@
+use nalgebra_glm::Vec3;
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{BufWriter, Error, ErrorKind, Write};
+use crate::obj_loader::Obj;
+
+// STL en texto o binario. STL no comparte vértices entre triángulos: se unen
+// las posiciones idénticas y las normales se generan (ángulo de pliegue por
+// defecto), porque las normales de faceta de muchos exportadores no son fiables
+pub fn load(filename: &str) -> Result<Obj, Error> {
+    let data = std::fs::read(filename)?;
+
+    let triangles = if is_binary(&data) {
+        read_binary(&data)
+    } else {
+        read_ascii(&String::from_utf8_lossy(&data))
+    }
+    .ok_or_else(|| Error::new(ErrorKind::InvalidData, format!("{}: STL inválido", filename)))?;
+
+    let mut vertices = Vec::new();
+    let mut indices = Vec::with_capacity(triangles.len() * 3);
+    let mut seen: HashMap<[u32; 3], u32> = HashMap::new();
+
+    for position in triangles.iter().flatten() {
+        let key = [position.x.to_bits(), position.y.to_bits(), position.z.to_bits()];
+        let index = *seen.entry(key).or_insert_with(|| {
+            vertices.push(*position);
+            (vertices.len() - 1) as u32
+        });
+        indices.push(index);
+    }
+
+    Ok(Obj::from_indexed(vertices, Vec::new(), Vec::new(), &indices))
+}
+
+// Un STL binario mide exactamente 84 + 50 bytes por triángulo; algunos
+// exportadores escriben "solid" en la cabecera binaria, así que no basta con
+// eso. Lo que no empieza con "solid" (sin distinguir mayúsculas, después de un
+// BOM o de espacios) se lee como binario y read_binary rechaza el tamaño si no
+// cuadra
+fn is_binary(data: &[u8]) -> bool {
+    if data.len() < 84 {
+        return false;
+    }
+    binary_size(data) == Some(data.len()) || !starts_with_solid(data)
+}
+
+fn starts_with_solid(data: &[u8]) -> bool {
+    let text = data.strip_prefix(b"\xEF\xBB\xBF").unwrap_or(data);
+    let start = text.iter().position(|b| !b.is_ascii_whitespace()).unwrap_or(text.len());
+    text[start..].get(..5).is_some_and(|prefix| prefix.eq_ignore_ascii_case(b"solid"))
+}
+
+// Tamaño que debería tener según la cantidad de triángulos de la cabecera
+fn binary_size(data: &[u8]) -> Option<usize> {
+    let count = u32::from_le_bytes(data.get(80..84)?.try_into().ok()?) as usize;
+    count.checked_mul(50)?.checked_add(84)
+}
+
+fn read_binary(data: &[u8]) -> Option<Vec<[Vec3; 3]>> {
+    // La cantidad viene del archivo: no se reserva memoria hasta comprobar que
+    // los triángulos están ahí (un archivo truncado o corrupto da None)
+    let size = binary_size(data)?;
+    if data.len() < size {
+        return None;
+    }
+    let count = (size - 84) / 50;
+    let float = |offset: usize| -> Option<f32> { Some(f32::from_le_bytes(data.get(offset..offset + 4)?.try_into().ok()?)) };
+
+    let mut triangles = Vec::with_capacity(count);
+    for t in 0..count {
+        // 12 bytes de normal de faceta, 3 vértices y 2 bytes de atributos
+        let base = 84 + t * 50 + 12;
+        let mut triangle = [Vec3::zeros(); 3];
+        for (i, vertex) in triangle.iter_mut().enumerate() {
+            let offset = base + i * 12;
+            *vertex = Vec3::new(float(offset)?, float(offset + 4)?, float(offset + 8)?);
+        }
+        triangles.push(triangle);
+    }
+    Some(triangles)
+}
+
+// None si no hay ninguna faceta o si alguna no tiene exactamente 3 vértices:
+// un archivo de texto cualquiera que empiece con "solid" no es una malla vacía
+fn read_ascii(text: &str) -> Option<Vec<[Vec3; 3]>> {
+    let mut triangles = Vec::new();
+    let mut corners = Vec::with_capacity(3);
+
+    for line in text.lines() {
+        let parts: Vec<String> = line.split_whitespace().map(str::to_ascii_lowercase).collect();
+        let parts: Vec<&str> = parts.iter().map(String::as_str).collect();
+        match parts.as_slice() {
+            ["vertex", x, y, z] => {
+                corners.push(Vec3::new(x.parse().ok()?, y.parse().ok()?, z.parse().ok()?));
+            }
+            ["endfacet"] => {
+                if corners.len() != 3 {
+                    return None;
+                }
+                triangles.push([corners[0], corners[1], corners[2]]);
+                corners.clear();
+            }
+            _ => {}
+        }
+    }
+
+    if triangles.is_empty() || !corners.is_empty() {
+        return None;
+    }
+    Some(triangles)
+}
+
+pub fn save(obj: &Obj, filename: &str, binary: bool) -> Result<(), Error> {
+    let vertex_array = obj.get_vertex_array();
+    let triangles: Vec<[Vec3; 3]> = vertex_array
+        .chunks_exact(3)
+        .map(|t| [t[0].position, t[1].position, t[2].position])
+        .collect();
+
+    let mut file = BufWriter::new(File::create(filename)?);
+
+    if binary {
+        let mut header = [0u8; 80];
+        header[..12].copy_from_slice(b"lab5-shaders");
+        file.write_all(&header)?;
+        file.write_all(&(triangles.len() as u32).to_le_bytes())?;
+
+        for triangle in &triangles {
+            let normal = facet_normal(triangle);
+            for v in std::iter::once(&normal).chain(triangle.iter()) {
+                for value in [v.x, v.y, v.z] {
+                    file.write_all(&value.to_le_bytes())?;
+                }
+            }
+            file.write_all(&[0u8; 2])?;
+        }
+    } else {
+        writeln!(file, "solid lab5-shaders")?;
+        for triangle in &triangles {
+            let n = facet_normal(triangle);
+            writeln!(file, "  facet normal {:.6} {:.6} {:.6}", n.x, n.y, n.z)?;
+            writeln!(file, "    outer loop")?;
+            for v in triangle {
+                writeln!(file, "      vertex {:.6} {:.6} {:.6}", v.x, v.y, v.z)?;
+            }
+            writeln!(file, "    endloop")?;
+            writeln!(file, "  endfacet")?;
+        }
+        writeln!(file, "endsolid lab5-shaders")?;
+    }
+
+    file.flush()
+}
+
+fn facet_normal(triangle: &[Vec3; 3]) -> Vec3 {
+    let normal = (triangle[1] - triangle[0]).cross(&(triangle[2] - triangle[0]));
+    if normal.norm() > 1e-12 {
+        normal.normalize()
+    } else {
+        Vec3::zeros()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_path(name: &str) -> String {
+        std::env::temp_dir().join(format!("lab5-stl-{}-{}", std::process::id(), name)).to_string_lossy().into_owned()
+    }
+
+    fn tetrahedron() -> Obj {
+        let vertices = vec![
+            Vec3::new(0.0, 0.0, 0.0),
+            Vec3::new(1.0, 0.0, 0.0),
+            Vec3::new(0.0, 1.0, 0.0),
+            Vec3::new(0.0, 0.0, 1.0),
+        ];
+        Obj::from_indexed(vertices, Vec::new(), Vec::new(), &[0, 2, 1, 0, 1, 3, 0, 3, 2, 1, 2, 3])
+    }
+
+    fn positions(obj: &Obj) -> Vec<[f32; 3]> {
+        obj.get_vertex_array().iter().map(|v| v.position.into()).collect()
+    }
+
+    #[test]
+    fn round_trip_ascii_and_binary() {
+        let obj = tetrahedron();
+        for binary in [false, true] {
+            let path = temp_path(if binary { "rt.bin.stl" } else { "rt.txt.stl" });
+            save(&obj, &path, binary).unwrap();
+            let loaded = load(&path).unwrap();
+            std::fs::remove_file(&path).unwrap();
+            assert_eq!(positions(&loaded), positions(&obj));
+        }
+    }
+
+    #[test]
+    fn truncated_binary_is_an_error() {
+        let path = temp_path("truncated.stl");
+        save(&tetrahedron(), &path, true).unwrap();
+        let data = std::fs::read(&path).unwrap();
+
+        std::fs::write(&path, &data[..data.len() - 20]).unwrap();
+        assert!(load(&path).is_err());
+
+        // Cabecera que pide 4 mil millones de triángulos sin tenerlos
+        let mut huge = data.clone();
+        huge[80..84].copy_from_slice(&u32::MAX.to_le_bytes());
+        std::fs::write(&path, &huge).unwrap();
+        assert!(load(&path).is_err());
+
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn ascii_detection_and_validation() {
+        let path = temp_path("ascii.stl");
+        save(&tetrahedron(), &path, false).unwrap();
+        let text = std::fs::read_to_string(&path).unwrap();
+
+        // BOM, espacios al principio y mayúsculas siguen siendo STL en texto
+        for variant in [format!("\u{FEFF}{}", text), format!("  \n{}", text), text.to_uppercase()] {
+            std::fs::write(&path, variant).unwrap();
+            assert_eq!(load(&path).unwrap().get_vertex_array().len(), 12);
+        }
+
+        // Texto que empieza con "solid" pero no tiene facetas, y una faceta
+        // con dos vértices
+        let broken = text.replacen("      vertex 0.000000 0.000000 0.000000\n", "", 1);
+        for invalid in ["solid nada\nendsolid nada\n".to_string() + &" ".repeat(100), broken] {
+            std::fs::write(&path, invalid).unwrap();
+            assert!(load(&path).is_err());
+        }
+
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,72 @@
+use nalgebra_glm::Vec3;
+
+// Tangentes por vértice con la misma convención que MikkTSpace: xyz es la
+// tangente (ortogonal a la normal) y w el signo de la bitangente, de modo que
+// bitangente = w * cross(normal, tangente)
+pub fn generate_tangents(
+    positions: &[[f32; 3]],
+    normals: &[[f32; 3]],
+    tex_coords: &[[f32; 2]],
+    indices: &[u32],
+) -> Vec<[f32; 4]> {
+    let mut tan1 = vec![Vec3::zeros(); positions.len()];
+    let mut tan2 = vec![Vec3::zeros(); positions.len()];
+
+    for triangle in indices.chunks_exact(3) {
+        let idx = [triangle[0] as usize, triangle[1] as usize, triangle[2] as usize];
+        if idx.iter().any(|&i| i >= positions.len() || i >= tex_coords.len()) {
+            continue;
+        }
+
+        let p = idx.map(|i| Vec3::from(positions[i]));
+        let uv = idx.map(|i| tex_coords[i]);
+
+        let e1 = p[1] - p[0];
+        let e2 = p[2] - p[0];
+        let du1 = uv[1][0] - uv[0][0];
+        let dv1 = uv[1][1] - uv[0][1];
+        let du2 = uv[2][0] - uv[0][0];
+        let dv2 = uv[2][1] - uv[0][1];
+
+        let det = du1 * dv2 - du2 * dv1;
+        if det.abs() < 1e-12 {
+            continue;
+        }
+        let r = 1.0 / det;
+        let sdir = (e1 * dv2 - e2 * dv1) * r;
+        let tdir = (e2 * du1 - e1 * du2) * r;
+
+        // Como MikkTSpace, cada triángulo aporta según su ángulo en el vértice
+        for corner in 0..3 {
+            let to_next = p[(corner + 1) % 3] - p[corner];
+            let to_prev = p[(corner + 2) % 3] - p[corner];
+            if to_next.norm() < 1e-12 || to_prev.norm() < 1e-12 {
+                continue;
+            }
+            let angle = to_next.normalize().dot(&to_prev.normalize()).clamp(-1.0, 1.0).acos();
+
+            tan1[idx[corner]] += sdir * angle;
+            tan2[idx[corner]] += tdir * angle;
+        }
+    }
+
+    (0..positions.len())
+        .map(|i| {
+            let n = normals.get(i).map(|&n| Vec3::from(n)).unwrap_or(Vec3::y());
+            let n = if n.norm() > 1e-12 { n.normalize() } else { Vec3::y() };
+
+            // Gram-Schmidt contra la normal
+            let t = tan1[i] - n * n.dot(&tan1[i]);
+            let t = if t.norm() > 1e-8 { t.normalize() } else { any_perpendicular(&n) };
+
+            let w = if n.cross(&t).dot(&tan2[i]) < 0.0 { -1.0 } else { 1.0 };
+            [t.x, t.y, t.z, w]
+        })
+        .collect()
+}
+
+// Para vértices sin coordenadas de textura útiles
+fn any_perpendicular(n: &Vec3) -> Vec3 {
+    let axis = if n.y.abs() < 0.99 { Vec3::y() } else { Vec3::x() };
+    axis.cross(n).normalize()
+}
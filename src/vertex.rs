@@ -1,10 +1,12 @@
-use nalgebra_glm::Vec3;
+use nalgebra_glm::{Vec3, Vec4};
 
 #[derive(Debug, Clone)]
 pub struct Vertex {
     pub position: Vec3,
     pub normal: Vec3,
     pub tex_coords: Vec3,
+    // xyz tangente, w signo de la bitangente (convención MikkTSpace)
+    pub tangent: Vec4,
     pub transformed_position: Vec3,
     pub transformed_normal: Vec3,
 }
@@ -15,8 +17,13 @@ impl Vertex {
             position,
             normal,
             tex_coords,
+            tangent: Vec4::zeros(),
             transformed_position: position,
             transformed_normal: normal,
         }
     }
+
+    pub fn bitangent(&self) -> Vec3 {
+        self.normal.cross(&self.tangent.xyz()) * self.tangent.w
+    }
 }